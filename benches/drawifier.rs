@@ -0,0 +1,135 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use glam::{Mat2, Vec2};
+use palette::{blend::Compose, Srgba};
+use pixel_renderer::{
+    camera::Camera,
+    drawing::{BresenhamCircle, Circle, LineBuilder, Pixel, Shape2D, WuLine},
+    renderer::{Background, Drawifier, Renderer},
+};
+
+const WIDTH: u32 = 512;
+const HEIGHT: u32 = 512;
+
+/// Shapes from `examples/2d-drawing.rs`
+fn drawing_scene() -> Vec<Shape2D> {
+    vec![
+        BresenhamCircle::new((200, 200), 100, Srgba::new(1f32, 0f32, 0f32, 1f32)).into(),
+        BresenhamCircle::new((350, 220), 40, Srgba::new(0.6f32, 1f32, 0.9f32, 1f32)).into(),
+        LineBuilder::<WuLine>::new()
+            .from((130, 400))
+            .to((160, 305))
+            .to((190, 400))
+            .close()
+            .from((170, 340))
+            .to((190, 320))
+            .to((240, 400))
+            .shape(),
+    ]
+}
+
+/// Shapes from `examples/pythagoras.rs` with a deeper tree
+fn pythagoras_scene() -> Vec<Shape2D> {
+    fn rotate(v: Vec2, degrees: f32) -> Vec2 {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Mat2::from_cols(Vec2::new(cos, sin), Vec2::new(-sin, cos)) * v
+    }
+
+    fn tree(iters: u32, p1: Vec2, p2: Vec2, shapes: &mut Vec<Shape2D>) {
+        let d = p1 - p2;
+        let r = rotate(d.normalize(), 90f32) * d.length();
+        let (p3, p4) = (p2 + r, p1 + r);
+        let c = p3 - p4;
+        let p5 = rotate(c.normalize(), -45f32) * (c.length() / 2f32.sqrt()) + p4;
+
+        let i = |v: Vec2| (v.x as i32, v.y as i32);
+        shapes.push(
+            LineBuilder::<WuLine>::new()
+                .from(i(p1))
+                .to(i(p2))
+                .to(i(p3))
+                .to(i(p4))
+                .close()
+                .shape(),
+        );
+        shapes.push(
+            LineBuilder::<WuLine>::new()
+                .from(i(p4))
+                .to(i(p5))
+                .to(i(p3))
+                .close()
+                .shape(),
+        );
+
+        if iters > 0 {
+            tree(iters - 1, p4, p5, shapes);
+            tree(iters - 1, p5, p3, shapes);
+        }
+    }
+
+    let mut shapes = vec![];
+    let (w, h) = (WIDTH as f32, HEIGHT as f32);
+    tree(
+        9,
+        Vec2::new(w / 2f32 - 50f32, h),
+        Vec2::new(w / 2f32 + 50f32, h),
+        &mut shapes,
+    );
+    shapes
+}
+
+/// Renders shapes stored as pixel vectors the way the drawifier did before spans: every
+/// shape is cloned into a boxed iterator and blended one pixel at a time.
+fn render_per_pixel(shapes: &[Vec<Pixel>], frame: &mut [&mut [u8]]) {
+    for pixel in &mut *frame {
+        pixel.copy_from_slice(&[0, 0, 0, 0xff]);
+    }
+
+    let boxed =
+        |s: &Vec<Pixel>| -> Box<dyn Iterator<Item = Pixel>> { Box::new(s.clone().into_iter()) };
+    for p in shapes.iter().flat_map(boxed) {
+        if !(0..WIDTH as i32).contains(&p.x()) || !(0..HEIGHT as i32).contains(&p.y()) {
+            continue;
+        }
+        let idx = WIDTH as usize * p.y() as usize + p.x() as usize;
+        let dest = &frame[idx];
+        let dest: Srgba<f32> = Srgba::new(dest[0], dest[1], dest[2], dest[3]).into_format();
+        let dest: [u8; 4] = p.color().over(dest).into_format().into();
+        frame[idx].copy_from_slice(&dest);
+    }
+}
+
+fn bench_scenes(c: &mut Criterion) {
+    let drawifier = Drawifier {
+        output_width: WIDTH,
+        output_height: HEIGHT,
+        background: Background::default(),
+    };
+    let camera = Camera::default();
+    let mut buffer = vec![0u8; WIDTH as usize * HEIGHT as usize * 4];
+
+    for (name, scene) in [
+        ("2d-drawing", drawing_scene as fn() -> Vec<Shape2D>),
+        ("pythagoras", pythagoras_scene),
+    ] {
+        let shapes = scene();
+        let pixels: Vec<Vec<Pixel>> = shapes.iter().map(|s| s.pixels().collect()).collect();
+        let mut group = c.benchmark_group(name);
+        group.bench_function("build", |b| b.iter(|| black_box(scene())));
+        group.bench_function("render_spans", |b| {
+            b.iter(|| {
+                let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+                drawifier.render(&camera, black_box(&shapes), &mut frame);
+            })
+        });
+        group.bench_function("render_per_pixel", |b| {
+            b.iter(|| {
+                let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+                render_per_pixel(black_box(&pixels), &mut frame);
+            })
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_scenes);
+criterion_main!(benches);
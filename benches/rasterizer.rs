@@ -0,0 +1,136 @@
+use std::sync::Arc;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use glam::Vec3;
+use palette::Srgba;
+use pixel_renderer::{
+    camera::Camera,
+    formats::ObjModel,
+    primitives::Torus,
+    renderer::{Background, FragmentShader, Mesh3D, Rasterizer, Renderer, Transparency},
+};
+
+const WIDTH: u32 = 512;
+const HEIGHT: u32 = 512;
+
+/// The models the 3D example shows, named for the benchmark ids.
+///
+/// Like the example, the OBJ file at `BENCH_OBJ` is loaded if it is set, the torus shown
+/// without a file is used otherwise, along with finer versions of it.
+fn example_models() -> Vec<(String, Vec<Box<dyn Mesh3D>>)> {
+    if let Ok(path) = std::env::var("BENCH_OBJ") {
+        let model = ObjModel::load(&path).unwrap_or_else(|e| panic!("Could not load {path}: {e}"));
+        return vec![(path, model.into_renderables())];
+    }
+    [(32, 16), (128, 64), (512, 256)]
+        .into_iter()
+        .map(|(segments, sides)| {
+            let torus = Torus {
+                segments,
+                sides,
+                color: Srgba::new(0.9f32, 0.6f32, 0.2f32, 1f32),
+                ..Default::default()
+            };
+            let name = format!("torus {} triangles", 2 * segments * sides);
+            (name, vec![Box::new(torus.mesh()) as Box<dyn Mesh3D>])
+        })
+        .collect()
+}
+
+/// Camera of the 3D example
+fn example_camera() -> Camera {
+    Camera {
+        position: Vec3::new(0f32, 0f32, 2f32),
+        ..Default::default()
+    }
+}
+
+/// Renderer of the 3D example, with its diffuse lighting
+fn example_rasterizer() -> Rasterizer {
+    let light = Vec3::new(-1f32, 1f32, 1f32).normalize();
+    let lighting: FragmentShader = Arc::new(move |fragment| {
+        let color = fragment
+            .color()
+            .unwrap_or(Srgba::new(1f32, 1f32, 1f32, 1f32));
+        let Some(normal) = fragment.normal() else {
+            return color;
+        };
+        let diffuse = 0.25f32 + 0.75f32 * normal.dot(light).max(0f32);
+        Srgba::new(
+            color.red * diffuse,
+            color.green * diffuse,
+            color.blue * diffuse,
+            color.alpha,
+        )
+    });
+    Rasterizer {
+        background: Background::VerticalGradient {
+            top: Srgba::new(0.1f32, 0.1f32, 0.2f32, 1f32),
+            bottom: Srgba::new(0f32, 0f32, 0f32, 1f32),
+        },
+        transparency: Transparency::SortTriangles,
+        fragment_shader: Some(lighting),
+        ..Rasterizer::new(WIDTH, HEIGHT)
+    }
+}
+
+fn bench_rasterizer(c: &mut Criterion) {
+    let camera = example_camera();
+    let rasterizer = example_rasterizer();
+    let mut buffer = vec![0u8; WIDTH as usize * HEIGHT as usize * 4];
+
+    let mut group = c.benchmark_group("rasterizer");
+    for (model, objects) in example_models() {
+        for threads in [1, 0] {
+            // 0 lets rayon use every core
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            let name = if threads == 1 {
+                "1 thread"
+            } else {
+                "all threads"
+            };
+            group.bench_with_input(BenchmarkId::new(name, &model), &objects, |b, objects| {
+                pool.install(|| {
+                    b.iter(|| {
+                        let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+                        rasterizer.render(&camera, black_box(objects), &mut frame);
+                    })
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+/// The models covering a few pixels, where handling the meshes outweighs shading
+fn bench_distant(c: &mut Criterion) {
+    let mut camera = Camera {
+        position: Vec3::new(0f32, 0f32, 50f32),
+        far: 100f32,
+        ..example_camera()
+    };
+    camera.look_at(Vec3::ZERO, Vec3::Y);
+    let rasterizer = example_rasterizer();
+    let mut buffer = vec![0u8; WIDTH as usize * HEIGHT as usize * 4];
+
+    let mut group = c.benchmark_group("rasterizer distant");
+    for (model, objects) in example_models() {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(&model),
+            &objects,
+            |b, objects| {
+                b.iter(|| {
+                    let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+                    rasterizer.render(&camera, black_box(objects), &mut frame);
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_rasterizer, bench_distant);
+criterion_main!(benches);
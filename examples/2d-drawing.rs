@@ -1,8 +1,7 @@
 use palette::Srgba;
 use pixel_renderer::{
-    camera::Camera,
     drawing::{BresenhamCircle, Circle, LineBuilder, WuLine},
-    renderer::{Drawifier, World},
+    renderer::{Background, Drawifier, Renderer, Scene2D},
 };
 use pixels::{PixelsBuilder, SurfaceTexture};
 use winit::{
@@ -35,26 +34,33 @@ fn main() {
             .unwrap()
     };
 
-    let mut world = World {
-        camera: Camera::default(),
-        renderer: Drawifier {
-            output_width: width,
-            output_height: height,
-        },
-        objects: vec![
-            BresenhamCircle::new((200, 200), 100, Srgba::new(1f32, 0f32, 0f32, 1f32)).into(),
-            BresenhamCircle::new((350, 220), 40, Srgba::new(0.6f32, 1f32, 0.9f32, 1f32)).into(),
-            LineBuilder::<WuLine>::new()
-                .from((130, 400))
-                .to((160, 305))
-                .to((190, 400))
-                .close()
-                .from((170, 340))
-                .to((190, 320))
-                .to((240, 400))
-                .shape(),
-        ],
+    let mut drawifier = Drawifier {
+        output_width: width,
+        output_height: height,
+        background: Background::default(),
     };
+    let mut scene = Scene2D::new();
+    scene.add_named(
+        "big circle",
+        Some("circles"),
+        BresenhamCircle::new((200, 200), 100, Srgba::new(1f32, 0f32, 0f32, 1f32)).into(),
+    );
+    scene.add_named(
+        "small circle",
+        Some("circles"),
+        BresenhamCircle::new((350, 220), 40, Srgba::new(0.6f32, 1f32, 0.9f32, 1f32)).into(),
+    );
+    scene.add(
+        LineBuilder::<WuLine>::new()
+            .from((130, 400))
+            .to((160, 305))
+            .to((190, 400))
+            .close()
+            .from((170, 340))
+            .to((190, 320))
+            .to((240, 400))
+            .shape(),
+    );
 
     if let Err(e) = event_loop.run(move |event, elwt| match event {
         Event::WindowEvent {
@@ -74,15 +80,16 @@ fn main() {
             pixels
                 .resize_buffer(size.width, size.height)
                 .expect("Error resizing pixel buffer.");
-            world.renderer.output_width = size.width;
-            world.renderer.output_height = size.height;
+            drawifier.set_output_dimensions(size.width, size.height);
+            scene.invalidate();
         }
         Event::WindowEvent {
             event: WindowEvent::RedrawRequested,
             ..
         } => {
             let mut frame: Vec<&mut [u8]> = pixels.frame_mut().chunks_exact_mut(4).collect();
-            world.render(&mut frame);
+            // Only the shapes that changed since the last frame get repainted
+            drawifier.render_dirty(&mut scene, &mut frame);
             pixels.render().expect("Error rendering frame.");
         }
         _ => (),
@@ -1,22 +1,43 @@
-use glam::Vec3;
-use palette::{Srgb, Srgba};
+use glam::{Vec2, Vec3};
+use palette::Srgba;
 use pixel_renderer::{
-    camera::{Camera, FitStrategy},
-    renderer::{Mesh3D, Rasterizer, VertexAttribute, World},
+    camera::{Camera, FitStrategy, Projection},
+    controller::{CameraController, CameraInput, FlyController},
+    formats::{read_ply, read_stl, GltfScene, ObjModel},
+    primitives::Torus,
+    renderer::{Background, FragmentShader, Mesh3D, Rasterizer, RenderMode, Transparency, World},
 };
 use pixels::{PixelsBuilder, SurfaceTexture};
 use radians::Rad32;
 use winit::{
-    dpi::{LogicalSize, PhysicalPosition},
+    dpi::{LogicalPosition, LogicalSize},
     event::{ElementState, Event, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
     keyboard::{KeyCode, PhysicalKey},
     window::WindowBuilder,
 };
 
-use obj::{load_obj, Obj};
-use std::fs::File;
-use std::io::BufReader;
+use std::{collections::HashSet, sync::Arc};
+
+/// Lights meshes with normals from the upper left, the others keep their colors
+fn lighting() -> FragmentShader {
+    let light = Vec3::new(-1f32, 1f32, 1f32).normalize();
+    Arc::new(move |fragment| {
+        let color = fragment
+            .color()
+            .unwrap_or(Srgba::new(1f32, 1f32, 1f32, 1f32));
+        let Some(normal) = fragment.normal() else {
+            return color;
+        };
+        let diffuse = 0.25f32 + 0.75f32 * normal.dot(light).max(0f32);
+        Srgba::new(
+            color.red * diffuse,
+            color.green * diffuse,
+            color.blue * diffuse,
+            color.alpha,
+        )
+    })
+}
 
 fn main() {
     let width = 512;
@@ -42,169 +63,193 @@ fn main() {
     };
 
     let args: Vec<String> = std::env::args().collect();
-    let input =
-        BufReader::new(File::open(args[1].clone()).expect("Could not open file with given path"));
-    let obj = ObjWrapper(load_obj(input).expect("Could not load obj file at the given path"));
+    // Without a file there is a torus to look at
+    let path = args.get(1).map(String::as_str).unwrap_or_default();
 
-    let mut world = World {
+    let world = World {
         camera: Camera {
             aperture: (35, 24),
             focal_length: 10f32,
             near: 0.1f32,
             far: 10f32,
             fit_strategy: FitStrategy::Overscan,
+            projection: Projection::Perspective,
             position: Vec3::new(0f32, 0f32, 2f32),
             yaw: Rad32::new(-90f32),
             pitch: Rad32::new(0f32),
+            roll: Rad32::new(0f32),
         },
         renderer: Rasterizer {
-            output_width: width,
-            output_height: height,
-            show_wireframe: false,
-            show_polygons: true,
+            background: Background::VerticalGradient {
+                top: Srgba::new(0.1f32, 0.1f32, 0.2f32, 1f32),
+                bottom: Srgba::new(0f32, 0f32, 0f32, 1f32),
+            },
+            render_mode: RenderMode::Shaded,
+            transparency: Transparency::SortTriangles,
+            fragment_shader: Some(lighting()),
+            ..Rasterizer::new(width, height)
         },
-        objects: vec![Box::new(obj)],
+        objects: vec![],
+    };
+    let mut world = if path.is_empty() {
+        let torus = Torus {
+            color: Srgba::new(0.9f32, 0.6f32, 0.2f32, 1f32),
+            ..Default::default()
+        };
+        World {
+            objects: vec![Box::new(torus.mesh()) as Box<dyn Mesh3D>],
+            ..world
+        }
+    } else if path.ends_with(".gltf") || path.ends_with(".glb") {
+        let scene = GltfScene::load(path).unwrap_or_else(|e| panic!("Could not load {path}: {e}"));
+        // Keep the default camera if the scene has none
+        let camera = scene.cameras.first().copied().unwrap_or(world.camera);
+        World {
+            camera,
+            ..scene.into_world(world.renderer)
+        }
+    } else if path.ends_with(".stl") || path.ends_with(".ply") {
+        let file =
+            std::fs::File::open(path).unwrap_or_else(|e| panic!("Could not open {path}: {e}"));
+        let mut mesh = if path.ends_with(".stl") {
+            read_stl(std::io::BufReader::new(file)).map_err(|e| e.to_string())
+        } else {
+            read_ply(std::io::BufReader::new(file)).map_err(|e| e.to_string())
+        }
+        .unwrap_or_else(|e| panic!("Could not load {path}: {e}"));
+        // STL only has facet normals, smooth them except on hard edges
+        if path.ends_with(".stl") || mesh.normals.is_empty() {
+            mesh.compute_normals(Rad32::new(std::f32::consts::FRAC_PI_6));
+        }
+        World {
+            objects: vec![Box::new(mesh) as Box<dyn Mesh3D>],
+            ..world
+        }
+    } else {
+        let model = ObjModel::load(path).unwrap_or_else(|e| panic!("Could not load {path}: {e}"));
+        World {
+            objects: model.into_renderables(),
+            ..world
+        }
     };
 
+    let mut controller = FlyController::default();
+    let mut input = CameraInput::default();
+    let mut held_keys: HashSet<KeyCode> = HashSet::new();
     let mut last_time = std::time::Instant::now();
-    let mut last_cursor: Option<PhysicalPosition<f64>> = None;
+    let mut last_cursor: Option<LogicalPosition<f32>> = None;
     let mut lmb_pressed = false;
-    if let Err(e) = event_loop.run(move |event, elwt| {
-        let now = std::time::Instant::now();
-        let dt = (now - last_time).as_secs_f32();
-        last_time = now;
+    if let Err(e) = event_loop.run(move |event, elwt| match event {
+        Event::WindowEvent {
+            event: WindowEvent::CloseRequested,
+            ..
+        } => {
+            elwt.exit();
+        }
+        Event::WindowEvent {
+            event: WindowEvent::Resized(size),
+            ..
+        } => {
+            let size = size.to_logical(window.scale_factor());
+            pixels
+                .resize_surface(size.width, size.height)
+                .expect("Error resizing pixel surface.");
+            pixels
+                .resize_buffer(size.width, size.height)
+                .expect("Error resizing pixel buffer.");
+            world.renderer.output_width = size.width;
+            world.renderer.output_height = size.height;
+        }
+        Event::WindowEvent {
+            event: WindowEvent::RedrawRequested,
+            ..
+        } => {
+            let mut frame: Vec<&mut [u8]> = pixels.frame_mut().chunks_exact_mut(4).collect();
+            world.render(&mut frame);
+            pixels.render().expect("Error rendering frame.");
+        }
+        Event::AboutToWait => {
+            let now = std::time::Instant::now();
+            let dt = (now - last_time).as_secs_f32();
+            last_time = now;
 
-        match event {
-            Event::WindowEvent {
-                event: WindowEvent::CloseRequested,
-                ..
-            } => {
-                elwt.exit();
-            }
-            Event::WindowEvent {
-                event: WindowEvent::Resized(size),
-                ..
-            } => {
-                let size = size.to_logical(window.scale_factor());
-                pixels
-                    .resize_surface(size.width, size.height)
-                    .expect("Error resizing pixel surface.");
-                pixels
-                    .resize_buffer(size.width, size.height)
-                    .expect("Error resizing pixel buffer.");
-                world.renderer.output_width = size.width;
-                world.renderer.output_height = size.height;
-            }
-            Event::WindowEvent {
-                event: WindowEvent::RedrawRequested,
-                ..
-            } => {
-                let mut frame: Vec<&mut [u8]> = pixels.frame_mut().chunks_exact_mut(4).collect();
-                world.render(&mut frame);
-                pixels.render().expect("Error rendering frame.");
-            }
-            Event::WindowEvent { event, .. } => {
-                let forward = world.camera.forward();
-                let right = world.camera.right();
-                let camera_speed = 10000f32 * dt;
-                let sensitivity = 1000f32 * dt;
+            let axis = |positive: KeyCode, negative: KeyCode| {
+                held_keys.contains(&positive) as i32 as f32
+                    - held_keys.contains(&negative) as i32 as f32
+            };
+            input.movement = Vec3::new(
+                axis(KeyCode::KeyD, KeyCode::KeyA),
+                axis(KeyCode::KeyE, KeyCode::KeyQ),
+                axis(KeyCode::KeyW, KeyCode::KeyS),
+            );
 
-                match event {
-                    WindowEvent::KeyboardInput {
-                        event:
-                            KeyEvent {
-                                physical_key: PhysicalKey::Code(key_code),
-                                state: ElementState::Pressed,
-                                ..
-                            },
-                        ..
-                    } => {
-                        match key_code {
-                            // Movement
-                            KeyCode::KeyW => world.camera.position += camera_speed * forward,
-                            KeyCode::KeyA => world.camera.position -= camera_speed * right,
-                            KeyCode::KeyS => world.camera.position -= camera_speed * forward,
-                            KeyCode::KeyD => world.camera.position += camera_speed * right,
-                            KeyCode::KeyQ => world.camera.position -= camera_speed * Vec3::Y,
-                            KeyCode::KeyE => world.camera.position += camera_speed * Vec3::Y,
-                            _ => (),
-                        }
-                        window.request_redraw();
-                    }
-                    WindowEvent::MouseInput {
+            let output_dimensions = (world.renderer.output_width, world.renderer.output_height);
+            controller.update(&mut world.camera, &input, dt, output_dimensions);
+            // Mouse movement and scrolling are consumed, the cursor stays where it is
+            input = CameraInput {
+                cursor: input.cursor,
+                ..Default::default()
+            };
+            window.request_redraw();
+        }
+        Event::WindowEvent { event, .. } => match event {
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(key_code),
                         state,
-                        button: MouseButton::Left,
                         ..
-                    } => match state {
-                        ElementState::Pressed => lmb_pressed = true,
-                        ElementState::Released => lmb_pressed = false,
                     },
-                    WindowEvent::CursorMoved { position, .. } => {
-                        if lmb_pressed {
-                            if let Some(last_cursor) = last_cursor {
-                                let x_change = -(position.x - last_cursor.x) as f32;
-                                let y_change = (position.y - last_cursor.y) as f32;
-                                world.camera.yaw += Rad32::new(x_change) * sensitivity;
-                                world.camera.pitch += Rad32::new(y_change) * sensitivity;
-                                world.camera.pitch = world
-                                    .camera
-                                    .pitch
-                                    .clamp(-Rad32::QUARTER_TURN, Rad32::QUARTER_TURN);
-                                window.request_redraw();
+                ..
+            } => match state {
+                ElementState::Pressed => {
+                    held_keys.insert(key_code);
+                    match key_code {
+                        KeyCode::Digit1 => world.renderer.render_mode = RenderMode::Shaded,
+                        KeyCode::Digit2 => world.renderer.render_mode = RenderMode::DepthGrayscale,
+                        KeyCode::Digit3 => world.renderer.render_mode = RenderMode::DepthFalseColor,
+                        KeyCode::KeyL => {
+                            world.renderer.fragment_shader = match world.renderer.fragment_shader {
+                                Some(_) => None,
+                                None => Some(lighting()),
                             }
-                            last_cursor = Some(position);
-                        } else {
-                            last_cursor = None;
                         }
+                        _ => (),
                     }
-                    WindowEvent::MouseWheel {
-                        delta: MouseScrollDelta::LineDelta(_, y),
-                        ..
-                    } => {
-                        world.camera.focal_length += y * 100000f32 * dt;
-                        world.camera.focal_length = world.camera.focal_length.max(0f32);
-                        window.request_redraw();
+                }
+                ElementState::Released => {
+                    held_keys.remove(&key_code);
+                }
+            },
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => match state {
+                ElementState::Pressed => lmb_pressed = true,
+                ElementState::Released => lmb_pressed = false,
+            },
+            WindowEvent::CursorMoved { position, .. } => {
+                let position = position.to_logical::<f32>(window.scale_factor());
+                if lmb_pressed {
+                    if let Some(last_cursor) = last_cursor {
+                        input.look.x += position.x - last_cursor.x;
+                        input.look.y += position.y - last_cursor.y;
                     }
-                    _ => (),
                 }
+                last_cursor = Some(position);
+                input.cursor = Some(Vec2::new(position.x, position.y));
+            }
+            WindowEvent::MouseWheel {
+                delta: MouseScrollDelta::LineDelta(_, y),
+                ..
+            } => {
+                input.zoom += y;
             }
             _ => (),
-        }
+        },
+        _ => (),
     }) {
         eprint!("Event loop error: {e:?}");
     }
 }
-
-struct ObjWrapper(Obj);
-
-impl Mesh3D for ObjWrapper {
-    fn vertices(&self) -> Vec<Vec3> {
-        self.0
-            .vertices
-            .iter()
-            .map(|v| {
-                let p = v.position;
-                Vec3::new(p[0], p[1], p[2])
-            })
-            .collect()
-    }
-
-    fn indices(&self) -> Vec<(usize, usize, usize)> {
-        self.0
-            .indices
-            .chunks(3)
-            .map(|e| (e[0] as usize, e[1] as usize, e[2] as usize))
-            .collect()
-    }
-
-    fn attributes(&self) -> Vec<VertexAttribute> {
-        let n = self.0.vertices.len();
-
-        vec![
-            VertexAttribute {
-                color: Srgb::new(1f32, 1f32, 1f32),
-            };
-            n
-        ]
-    }
-}
@@ -2,7 +2,7 @@ use glam::{Mat2, Vec2};
 use pixel_renderer::{
     camera::Camera,
     drawing::{LineBuilder, WuLine},
-    renderer::{Drawifier, World},
+    renderer::{Background, Drawifier, World},
 };
 use pixels::{PixelsBuilder, SurfaceTexture};
 use winit::{
@@ -78,6 +78,7 @@ fn main() {
         renderer: Drawifier {
             output_width: width,
             output_height: height,
+            background: Background::default(),
         },
         objects: shapes,
     };
@@ -1,4 +1,4 @@
-use glam::{Mat4, Vec3};
+use glam::{EulerRot, Mat3, Mat4, Quat, Vec2, Vec3, Vec4};
 use radians::Rad32;
 
 #[allow(clippy::manual_non_exhaustive)]
@@ -9,10 +9,13 @@ pub struct Camera {
     pub near: f32,
     pub far: f32,
     pub fit_strategy: FitStrategy,
+    pub projection: Projection,
 
     pub position: Vec3,
     pub yaw: Rad32,
     pub pitch: Rad32,
+    /// Rotation around the direction the camera is looking in
+    pub roll: Rad32,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -21,6 +24,17 @@ pub enum FitStrategy {
     Overscan,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    Perspective,
+    /// Parallel projection showing `height` world units vertically.
+    /// The visible width follows from the aperture aspect ratio
+    /// and both are fit to the output with the camera's `FitStrategy`.
+    Orthographic {
+        height: f32,
+    },
+}
+
 impl Default for Camera {
     fn default() -> Self {
         Self {
@@ -29,41 +43,206 @@ impl Default for Camera {
             near: 0.1f32,
             far: 10f32,
             fit_strategy: FitStrategy::Overscan,
+            projection: Projection::Perspective,
             position: Vec3::ZERO,
             yaw: Rad32::new(-90f32),
             pitch: Rad32::new(0f32),
+            roll: Rad32::new(0f32),
         }
     }
 }
 
 impl Camera {
     pub fn canvas(&self, output_dimensions: (u32, u32)) -> Canvas {
-        Canvas::from_camera_parameters(
-            self.aperture,
-            self.focal_length,
-            self.near,
-            self.fit_strategy,
+        match self.projection {
+            Projection::Perspective => Canvas::from_camera_parameters(
+                self.aperture,
+                self.focal_length,
+                self.near,
+                self.fit_strategy,
+                output_dimensions,
+            ),
+            Projection::Orthographic { height } => {
+                Canvas::orthographic(self.aperture, height, self.fit_strategy, output_dimensions)
+            }
+        }
+    }
+
+    /// Matrix taking points from camera space into clip space.
+    /// Visible points end up with coordinates in [-1; 1] after division by `w`.
+    pub fn projection(&self, output_dimensions: (u32, u32)) -> Mat4 {
+        let canvas = self.canvas(output_dimensions);
+        let (near, far) = (self.near, self.far);
+        match self.projection {
+            Projection::Perspective => Mat4::from_cols(
+                Vec4::X * 2f32 * near / canvas.width,
+                Vec4::Y * 2f32 * near / canvas.height,
+                Vec4::NEG_Z * (far + near) / (far - near) + Vec4::NEG_W,
+                Vec4::NEG_Z * 2f32 * far * near / (far - near),
+            ),
+            Projection::Orthographic { .. } => Mat4::from_cols(
+                Vec4::X * 2f32 / canvas.width,
+                Vec4::Y * 2f32 / canvas.height,
+                Vec4::NEG_Z * 2f32 / (far - near),
+                Vec4::NEG_Z * (far + near) / (far - near) + Vec4::W,
+            ),
+        }
+    }
+
+    /// Matrix taking points from world space into clip space.
+    pub fn view_projection(&self, output_dimensions: (u32, u32)) -> Mat4 {
+        self.projection(output_dimensions) * self.world_to_camera()
+    }
+
+    /// Projects a point in world space to raster space.
+    /// Returns pixel coordinates in `x` and `y`, and depth in [-1; 1] between `near` and `far` in `z`.
+    pub fn project(&self, world_point: Vec3, output_dimensions: (u32, u32)) -> Vec3 {
+        Self::project_with(
+            &self.view_projection(output_dimensions),
+            world_point,
             output_dimensions,
         )
     }
 
+    /// Same as `project` with the `view_projection` of the camera computed once,
+    /// for projecting many points.
+    pub fn project_with(
+        view_projection: &Mat4,
+        world_point: Vec3,
+        output_dimensions: (u32, u32),
+    ) -> Vec3 {
+        // Important: point is now in homogenous coordinates
+        let v = *view_projection * Vec4::from((world_point, 1f32));
+        // Transform back from homogenous coordinates
+        let v = Vec3::new(v.x / v.w, v.y / v.w, v.z / v.w);
+        // Project normalized coordinates to raster space
+        let x_raster = (v.x + 1f32) / 2f32 * output_dimensions.0 as f32;
+        // Y is down in raster space but up in NDC, so invert it
+        let y_raster = (1f32 - v.y) / 2f32 * output_dimensions.1 as f32;
+        Vec3::new(x_raster, y_raster, v.z)
+    }
+
+    /// Distance in front of the camera of a point with `depth` as returned by `project`.
+    pub fn linear_depth(&self, depth: f32) -> f32 {
+        let (near, far) = (self.near, self.far);
+        match self.projection {
+            Projection::Perspective => 2f32 * far * near / (far + near - depth * (far - near)),
+            Projection::Orthographic { .. } => (depth + 1f32) / 2f32 * (far - near) + near,
+        }
+    }
+
+    /// Inverse of `project`: returns the world space point that ends up at pixel `screen_point`
+    /// with `depth`.
+    pub fn unproject(&self, screen_point: Vec2, depth: f32, output_dimensions: (u32, u32)) -> Vec3 {
+        let ndc = Vec4::new(
+            screen_point.x / output_dimensions.0 as f32 * 2f32 - 1f32,
+            1f32 - screen_point.y / output_dimensions.1 as f32 * 2f32,
+            depth,
+            1f32,
+        );
+        let v = self.view_projection(output_dimensions).inverse() * ndc;
+        Vec3::new(v.x / v.w, v.y / v.w, v.z / v.w)
+    }
+
+    /// Ray in world space covering every point drawn at pixel (`x`, `y`).
+    /// Returns the origin of the ray on the near plane and its normalized direction.
+    pub fn ray_through_pixel(&self, x: f32, y: f32, output_dimensions: (u32, u32)) -> (Vec3, Vec3) {
+        let near = self.unproject(Vec2::new(x, y), -1f32, output_dimensions);
+        let far = self.unproject(Vec2::new(x, y), 1f32, output_dimensions);
+        (near, (far - near).normalize())
+    }
+
+    /// Moves between this perspective camera at `t = 0` and an orthographic one at `t = 1`.
+    ///
+    /// Like in a dolly zoom, the camera backs away while zooming in so that
+    /// the plane `focus_distance` in front of it keeps its size on screen.
+    /// Orthographic cameras are returned unchanged.
+    pub fn dolly_zoom(&self, focus_distance: f32, t: f32) -> Camera {
+        if self.projection != Projection::Perspective {
+            return *self;
+        }
+
+        let t = t.clamp(0f32, 1f32);
+        // Past this point the camera is so far away that it might as well be orthographic
+        if t > 0.999f32 {
+            return Camera {
+                projection: Projection::Orthographic {
+                    height: self.aperture.1 as f32 / self.focal_length * focus_distance,
+                },
+                ..*self
+            };
+        }
+
+        let distance = focus_distance / (1f32 - t);
+        let offset = distance - focus_distance;
+        Camera {
+            focal_length: self.focal_length * distance / focus_distance,
+            near: self.near + offset,
+            far: self.far + offset,
+            position: self.position - self.forward() * offset,
+            ..*self
+        }
+    }
+
     pub fn world_to_camera(&self) -> Mat4 {
-        let (yaw_sin, yaw_cos) = self.yaw.sin_cos();
-        let (pitch_sin, pitch_cos) = self.pitch.sin_cos();
-        Mat4::look_to_rh(
-            self.position,
-            Vec3::new(yaw_cos * pitch_cos, pitch_sin, yaw_sin * pitch_cos).normalize(),
-            Vec3::Y,
-        )
+        Mat4::from_rotation_translation(self.orientation(), self.position).inverse()
+    }
+
+    /// Rotation taking camera space into world space.
+    /// The camera looks down its negative z axis with y pointing up.
+    pub fn orientation(&self) -> Quat {
+        // Zero yaw looks along positive x, while the camera looks along negative z
+        let heading = -(self.yaw.val() + std::f32::consts::FRAC_PI_2);
+        Quat::from_euler(EulerRot::YXZ, heading, self.pitch.val(), self.roll.val())
+    }
+
+    /// Sets `yaw`, `pitch` and `roll` so that the camera is rotated by `orientation`.
+    pub fn set_orientation(&mut self, orientation: Quat) {
+        let orientation = orientation.normalize();
+        let (mut heading, pitch, mut roll) = orientation.to_euler(EulerRot::YXZ);
+        // Looking straight up or down, heading and roll turn about the same axis
+        if (pitch.abs() - std::f32::consts::FRAC_PI_2).abs() < 1e-3f32 {
+            let right = orientation * Vec3::X;
+            heading = (-right.z).atan2(right.x);
+            roll = 0f32;
+        }
+        self.yaw = Rad32::new(-heading - std::f32::consts::FRAC_PI_2);
+        self.pitch = Rad32::new(pitch);
+        self.roll = Rad32::new(roll);
+    }
+
+    /// Turns the camera towards `target`, keeping its top as close to `up` as possible.
+    pub fn look_at(&mut self, target: Vec3, up: Vec3) {
+        let forward = (target - self.position).normalize();
+        if !forward.is_finite() {
+            return;
+        }
+        // Looking along `up` leaves roll undefined, so keep the current top of the camera.
+        // If the camera is turning towards its top, the top tilts away from where it looked.
+        let parallel = |up: Vec3| forward.cross(up).length_squared() < 1e-8;
+        let up = if !parallel(up) {
+            up
+        } else if !parallel(self.up()) {
+            self.up()
+        } else {
+            -self.forward() * forward.dot(self.up()).signum()
+        };
+        let right = forward.cross(up).normalize();
+        let up = right.cross(forward);
+        self.set_orientation(Quat::from_mat3(&Mat3::from_cols(right, up, -forward)));
     }
 
+    /// Direction the camera is looking in
     pub fn forward(&self) -> Vec3 {
-        let (yaw_sin, yaw_cos) = self.yaw.sin_cos();
-        Vec3::new(yaw_cos, 0f32, yaw_sin).normalize()
+        self.orientation() * Vec3::NEG_Z
     }
 
     pub fn right(&self) -> Vec3 {
-        self.forward().cross(Vec3::Y).normalize()
+        self.orientation() * Vec3::X
+    }
+
+    pub fn up(&self) -> Vec3 {
+        self.orientation() * Vec3::Y
     }
 }
 
@@ -80,6 +259,31 @@ impl Canvas {
         near: f32,
         fit_strategy: FitStrategy,
         output_dimensions: (u32, u32),
+    ) -> Self {
+        let width = 2f32 * (aperture.0 as f32 / 2f32 / focal_length) * near;
+        Self::fit(aperture, width, fit_strategy, output_dimensions)
+    }
+
+    /// Canvas of an orthographic camera showing `height` units before fitting.
+    pub fn orthographic(
+        aperture: (u8, u8),
+        height: f32,
+        fit_strategy: FitStrategy,
+        output_dimensions: (u32, u32),
+    ) -> Self {
+        let width = height * aperture.0 as f32 / aperture.1 as f32;
+        Self::fit(aperture, width, fit_strategy, output_dimensions)
+    }
+
+    /// Scales a canvas `width` wide with the aperture aspect ratio to the output aspect ratio.
+    ///
+    /// The canvas always ends up with the aspect ratio of the output, so nothing is stretched:
+    /// `Fill` crops the aperture to the output and `Overscan` shows more around it.
+    fn fit(
+        aperture: (u8, u8),
+        width: f32,
+        fit_strategy: FitStrategy,
+        output_dimensions: (u32, u32),
     ) -> Self {
         let aperture_aspect_ratio = aperture.0 as f32 / aperture.1 as f32;
         let output_aspect_ratio = output_dimensions.0 as f32 / output_dimensions.1 as f32;
@@ -100,9 +304,9 @@ impl Canvas {
             }
         };
 
-        let width = 2f32 * (aperture.0 as f32 / 2f32 / focal_length) * near * x_scale;
-        let height = width / aperture_aspect_ratio * y_scale;
-
-        Self { width, height }
+        Self {
+            width: width * x_scale,
+            height: width / aperture_aspect_ratio * y_scale,
+        }
     }
 }
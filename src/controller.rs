@@ -0,0 +1,278 @@
+use glam::{Vec2, Vec3};
+use radians::Rad32;
+
+use crate::camera::Camera;
+
+/// Input gathered since the previous update, independent of the windowing library.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CameraInput {
+    /// Mouse movement in pixels, y is down like in raster space
+    pub look: Vec2,
+    /// Requested movement relative to the camera: x is right, y is up and z is forward.
+    /// Every component is expected to be in [-1; 1].
+    pub movement: Vec3,
+    /// Scroll steps, positive values zoom in
+    pub zoom: f32,
+    /// Cursor position in raster space, zooming moves towards it if present
+    pub cursor: Option<Vec2>,
+}
+
+pub trait CameraController {
+    /// Moves `camera` according to `input` received over the last `dt` seconds.
+    fn update(
+        &mut self,
+        camera: &mut Camera,
+        input: &CameraInput,
+        dt: f32,
+        output_dimensions: (u32, u32),
+    );
+}
+
+/// Fraction of the remaining distance to cover in `dt` seconds
+/// when approaching a target with time constant `smoothing`.
+fn smoothing_factor(smoothing: f32, dt: f32) -> f32 {
+    if smoothing <= 0f32 {
+        1f32
+    } else {
+        1f32 - (-dt / smoothing).exp()
+    }
+}
+
+/// Just shy of looking straight up or down, where yaw stops making sense
+const PITCH_LIMIT: f32 = std::f32::consts::FRAC_PI_2 - 0.001;
+
+/// Turns and moves a free flying camera along its own axes.
+#[derive(Debug, Clone, Copy)]
+pub struct FlyController {
+    /// Movement speed in units per second
+    pub speed: f32,
+    /// Rotation in radians per pixel of mouse movement
+    pub sensitivity: f32,
+    /// Distance in units covered by one scroll step
+    pub zoom_speed: f32,
+    /// Time constant in seconds for reaching the requested speed and rotation.
+    /// Larger values give smoother movement with more inertia, 0 disables smoothing.
+    pub smoothing: f32,
+    velocity: Vec3,
+    pending_look: Vec2,
+    pending_zoom: Vec3,
+}
+
+impl Default for FlyController {
+    fn default() -> Self {
+        Self {
+            speed: 2f32,
+            sensitivity: 0.005f32,
+            zoom_speed: 0.5f32,
+            smoothing: 0.1f32,
+            velocity: Vec3::ZERO,
+            pending_look: Vec2::ZERO,
+            pending_zoom: Vec3::ZERO,
+        }
+    }
+}
+
+impl FlyController {
+    /// Direction of the zoom, towards the cursor if there is one
+    fn zoom_direction(camera: &Camera, input: &CameraInput, output_dimensions: (u32, u32)) -> Vec3 {
+        match input.cursor {
+            Some(c) => camera.ray_through_pixel(c.x, c.y, output_dimensions).1,
+            None => camera.forward(),
+        }
+    }
+
+    fn look(&mut self, camera: &mut Camera, input: &CameraInput, dt: f32) {
+        self.pending_look += input.look * self.sensitivity;
+        let look = self.pending_look * smoothing_factor(self.smoothing, dt);
+        self.pending_look -= look;
+
+        camera.yaw += Rad32::new(look.x);
+        camera.pitch = Rad32::new((camera.pitch.val() - look.y).clamp(-PITCH_LIMIT, PITCH_LIMIT));
+    }
+
+    fn zoom(&mut self, camera: &mut Camera, input: &CameraInput, dt: f32, dims: (u32, u32)) {
+        if input.zoom != 0f32 {
+            self.pending_zoom +=
+                Self::zoom_direction(camera, input, dims) * input.zoom * self.zoom_speed;
+        }
+        let zoom = self.pending_zoom * smoothing_factor(self.smoothing, dt);
+        self.pending_zoom -= zoom;
+        camera.position += zoom;
+    }
+
+    fn accelerate(&mut self, target: Vec3, dt: f32) {
+        self.velocity += (target - self.velocity) * smoothing_factor(self.smoothing, dt);
+    }
+}
+
+impl CameraController for FlyController {
+    fn update(
+        &mut self,
+        camera: &mut Camera,
+        input: &CameraInput,
+        dt: f32,
+        output_dimensions: (u32, u32),
+    ) {
+        self.look(camera, input, dt);
+
+        let m = input.movement;
+        let target =
+            (camera.right() * m.x + camera.up() * m.y + camera.forward() * m.z) * self.speed;
+        self.accelerate(target, dt);
+        camera.position += self.velocity * dt;
+
+        self.zoom(camera, input, dt, output_dimensions);
+    }
+}
+
+/// Walks on the horizontal plane like in first person games.
+/// Zooming changes the focal length instead of moving the camera.
+#[derive(Debug, Clone, Copy)]
+pub struct FpsController {
+    pub fly: FlyController,
+    /// Focal length change per scroll step
+    pub focal_length_step: f32,
+    pub min_focal_length: f32,
+    pub max_focal_length: f32,
+}
+
+impl Default for FpsController {
+    fn default() -> Self {
+        Self {
+            fly: FlyController::default(),
+            focal_length_step: 1f32,
+            min_focal_length: 5f32,
+            max_focal_length: 100f32,
+        }
+    }
+}
+
+impl CameraController for FpsController {
+    fn update(
+        &mut self,
+        camera: &mut Camera,
+        input: &CameraInput,
+        dt: f32,
+        _output_dimensions: (u32, u32),
+    ) {
+        camera.roll = Rad32::new(0f32);
+        self.fly.look(camera, input, dt);
+
+        let (yaw_sin, yaw_cos) = camera.yaw.sin_cos();
+        let forward = Vec3::new(yaw_cos, 0f32, yaw_sin);
+        let right = forward.cross(Vec3::Y);
+        let m = input.movement;
+        let target = (right * m.x + Vec3::Y * m.y + forward * m.z) * self.fly.speed;
+        self.fly.accelerate(target, dt);
+        camera.position += self.fly.velocity * dt;
+
+        camera.focal_length = (camera.focal_length + input.zoom * self.focal_length_step)
+            .clamp(self.min_focal_length, self.max_focal_length);
+    }
+}
+
+/// Rotates the camera around a target point, pans the target and zooms towards it.
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitController {
+    pub target: Vec3,
+    pub distance: f32,
+    /// Angle around the vertical axis through the target
+    pub azimuth: f32,
+    /// Angle above the horizontal plane through the target
+    pub elevation: f32,
+    /// Rotation in radians per pixel of mouse movement
+    pub sensitivity: f32,
+    /// Panning speed in distances to the target per second
+    pub pan_speed: f32,
+    /// Fraction of the distance to the target covered by one scroll step
+    pub zoom_speed: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    /// Time constant in seconds for following the input, 0 disables smoothing
+    pub smoothing: f32,
+    pending_look: Vec2,
+    pending_zoom: f32,
+    velocity: Vec3,
+    zoom_cursor: Option<Vec2>,
+}
+
+impl OrbitController {
+    /// Orbits `target` from where `camera` currently is.
+    pub fn new(camera: &Camera, target: Vec3) -> Self {
+        let offset = camera.position - target;
+        let distance = offset.length().max(f32::EPSILON);
+        Self {
+            target,
+            distance,
+            azimuth: offset.z.atan2(offset.x),
+            elevation: (offset.y / distance).clamp(-1f32, 1f32).asin(),
+            sensitivity: 0.005f32,
+            pan_speed: 1f32,
+            zoom_speed: 0.1f32,
+            min_distance: 0.01f32,
+            max_distance: f32::INFINITY,
+            smoothing: 0.1f32,
+            pending_look: Vec2::ZERO,
+            pending_zoom: 0f32,
+            velocity: Vec3::ZERO,
+            zoom_cursor: None,
+        }
+    }
+}
+
+impl CameraController for OrbitController {
+    fn update(
+        &mut self,
+        camera: &mut Camera,
+        input: &CameraInput,
+        dt: f32,
+        output_dimensions: (u32, u32),
+    ) {
+        let t = smoothing_factor(self.smoothing, dt);
+
+        self.pending_look += input.look * self.sensitivity;
+        let look = self.pending_look * t;
+        self.pending_look -= look;
+        self.azimuth += look.x;
+        self.elevation = (self.elevation + look.y).clamp(-PITCH_LIMIT, PITCH_LIMIT);
+
+        // Panning moves the target in the plane of the screen
+        let m = input.movement;
+        let target_velocity =
+            (camera.right() * m.x + camera.up() * m.y) * self.pan_speed * self.distance;
+        self.velocity += (target_velocity - self.velocity) * t;
+        self.target += self.velocity * dt;
+
+        if input.zoom != 0f32 {
+            self.pending_zoom += input.zoom;
+            self.zoom_cursor = input.cursor;
+        }
+        let zoom = self.pending_zoom * t;
+        self.pending_zoom -= zoom;
+        let distance = (self.distance * (1f32 - self.zoom_speed).powf(zoom))
+            .clamp(self.min_distance, self.max_distance);
+        if let Some(cursor) = self.zoom_cursor {
+            // Move the target towards the point under the cursor on the plane through the target,
+            // so that this point stays under the cursor
+            let (origin, direction) =
+                camera.ray_through_pixel(cursor.x, cursor.y, output_dimensions);
+            let forward = camera.forward();
+            let along = (self.target - origin).dot(forward) / direction.dot(forward);
+            if along.is_finite() && along > 0f32 {
+                let point = origin + direction * along;
+                self.target += (point - self.target) * (1f32 - distance / self.distance);
+            }
+        }
+        self.distance = distance;
+
+        let (azimuth_sin, azimuth_cos) = self.azimuth.sin_cos();
+        let (elevation_sin, elevation_cos) = self.elevation.sin_cos();
+        let offset = Vec3::new(
+            azimuth_cos * elevation_cos,
+            elevation_sin,
+            azimuth_sin * elevation_cos,
+        );
+        camera.position = self.target + offset * self.distance;
+        camera.look_at(self.target, Vec3::Y);
+    }
+}
@@ -3,7 +3,7 @@ mod line;
 
 pub use circle::*;
 pub use line::*;
-use palette::Srgba;
+use palette::{Srgb, Srgba, WithAlpha};
 
 #[derive(Clone, Copy, Debug)]
 pub struct Pixel {
@@ -12,42 +12,186 @@ pub struct Pixel {
     pub(crate) color: Srgba,
 }
 
-// pub type Shape2D = Vec<Pixel>;
+impl Pixel {
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+
+    pub fn color(&self) -> Srgba {
+        self.color
+    }
+}
+
+/// A horizontal run of pixels in row `y` covering `x_start..x_end`.
+/// Every pixel of the run is drawn with the same coverage of the shape's paint.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Span {
+    pub(crate) y: i32,
+    pub(crate) x_start: i32,
+    pub(crate) x_end: i32,
+    pub(crate) coverage: f32,
+}
+
+/// An axis aligned rectangle in raster space covering `x0..x1` and `y0..y1`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub x0: i32,
+    pub y0: i32,
+    pub x1: i32,
+    pub y1: i32,
+}
+
+impl Rect {
+    pub fn new(x0: i32, y0: i32, x1: i32, y1: i32) -> Self {
+        Self { x0, y0, x1, y1 }
+    }
+
+    pub fn width(&self) -> i32 {
+        (self.x1 - self.x0).max(0)
+    }
+
+    pub fn height(&self) -> i32 {
+        (self.y1 - self.y0).max(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x0 >= self.x1 || self.y0 >= self.y1
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        (self.x0..self.x1).contains(&x) && (self.y0..self.y1).contains(&y)
+    }
+
+    /// Smallest rectangle containing both `self` and `other`
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect {
+            x0: self.x0.min(other.x0),
+            y0: self.y0.min(other.y0),
+            x1: self.x1.max(other.x1),
+            y1: self.y1.max(other.y1),
+        }
+    }
+
+    /// Overlapping part of `self` and `other`, `None` if they do not overlap
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let r = Rect {
+            x0: self.x0.max(other.x0),
+            y0: self.y0.max(other.y0),
+            x1: self.x1.min(other.x1),
+            y1: self.y1.min(other.y1),
+        };
+        (!r.is_empty()).then_some(r)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Shape2D {
     Pixel(Pixel),
-    Complex(Vec<Pixel>),
+    /// Runs of pixels painted with a single color
+    Spans {
+        paint: Srgba,
+        spans: Vec<Span>,
+    },
+    /// Several shapes drawn one after another
+    Compound(Vec<Shape2D>),
 }
 
-impl<I: Iterator<Item = Pixel>> From<I> for Shape2D {
-    fn from(value: I) -> Self {
-        Shape2D::Complex(value.collect())
+impl Shape2D {
+    /// Returns an iterator over every pixel of the shape.
+    pub fn pixels(&self) -> Box<dyn Iterator<Item = Pixel> + '_> {
+        match self {
+            Shape2D::Pixel(p) => Box::new(std::iter::once(*p)),
+            Shape2D::Spans { paint, spans } => Box::new(spans.iter().flat_map(move |s| {
+                let color = paint.with_alpha(paint.alpha * s.coverage);
+                (s.x_start..s.x_end).map(move |x| Pixel { x, y: s.y, color })
+            })),
+            Shape2D::Compound(shapes) => Box::new(shapes.iter().flat_map(|s| s.pixels())),
+        }
     }
-}
 
-impl IntoIterator for Shape2D {
-    type Item = Pixel;
+    /// Smallest rectangle containing every pixel of the shape, `None` for empty shapes.
+    pub fn bounds(&self) -> Option<Rect> {
+        match self {
+            Shape2D::Pixel(p) => Some(Rect::new(p.x, p.y, p.x + 1, p.y + 1)),
+            Shape2D::Spans { spans, .. } => spans
+                .iter()
+                .map(|s| Rect::new(s.x_start, s.y, s.x_end, s.y + 1))
+                .reduce(|a, b| a.union(&b)),
+            Shape2D::Compound(shapes) => shapes
+                .iter()
+                .filter_map(|s| s.bounds())
+                .reduce(|a, b| a.union(&b)),
+        }
+    }
 
-    type IntoIter = Box<dyn Iterator<Item = Self::Item>>;
+    /// Packs pixels of a single color into spans.
+    /// Pixels are sorted by row and adjacent pixels with equal coverage are merged.
+    fn from_single_color(paint: Srgb, mut pixels: Vec<Pixel>) -> Self {
+        pixels.sort_by_key(|p| (p.y, p.x));
 
-    fn into_iter(self) -> Self::IntoIter {
-        match self {
-            Shape2D::Pixel(p) => Box::new(std::iter::once(p)),
-            Shape2D::Complex(v) => Box::new(v.into_iter()),
+        let mut spans: Vec<Span> = Vec::with_capacity(pixels.len());
+        for p in pixels {
+            let coverage = p.color.alpha;
+            match spans.last_mut() {
+                Some(s) if s.y == p.y && s.x_end == p.x && s.coverage == coverage => s.x_end += 1,
+                _ => spans.push(Span {
+                    y: p.y,
+                    x_start: p.x,
+                    x_end: p.x + 1,
+                    coverage,
+                }),
+            }
+        }
+
+        Shape2D::Spans {
+            paint: paint.with_alpha(1f32),
+            spans,
         }
     }
 }
 
+impl<I: Iterator<Item = Pixel>> From<I> for Shape2D {
+    fn from(value: I) -> Self {
+        value.collect()
+    }
+}
+
 impl FromIterator<Pixel> for Shape2D {
     fn from_iter<T: IntoIterator<Item = Pixel>>(iter: T) -> Self {
-        let mut c = vec![];
-        for e in iter {
-            c.push(e);
+        let mut pixels = iter.into_iter();
+        let Some(first) = pixels.next() else {
+            return Self::Compound(vec![]);
+        };
+        let Some(second) = pixels.next() else {
+            return Self::Pixel(first);
+        };
+
+        // Group runs of pixels with the same color, alpha is turned into span coverage.
+        // Groups keep the order of the pixels, within a group it does not matter as blending
+        // a color over itself gives the same result either way.
+        let mut groups: Vec<(Srgb, Vec<Pixel>)> = vec![];
+        for p in [first, second].into_iter().chain(pixels) {
+            let paint = p.color.without_alpha();
+            match groups.last_mut() {
+                Some((c, group)) if *c == paint => group.push(p),
+                _ => groups.push((paint, vec![p])),
+            }
         }
-        if c.len() == 1 {
-            Self::Pixel(c[0])
+
+        if groups.len() == 1 {
+            let (paint, pixels) = groups.pop().unwrap();
+            Self::from_single_color(paint, pixels)
         } else {
-            Self::Complex(c)
+            Self::Compound(
+                groups
+                    .into_iter()
+                    .map(|(paint, pixels)| Self::from_single_color(paint, pixels))
+                    .collect(),
+            )
         }
     }
 }
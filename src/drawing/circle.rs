@@ -1,6 +1,6 @@
 use palette::Srgba;
 
-use super::{Pixel, Shape2D};
+use super::Pixel;
 
 pub trait Circle: Iterator<Item = Pixel> {
     fn new(c: (i32, i32), r: i32, color: Srgba) -> Self
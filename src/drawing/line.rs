@@ -1,6 +1,5 @@
 use std::marker::PhantomData;
 
-use itertools::Itertools;
 use palette::{Srgba, WithAlpha};
 
 use super::{Pixel, Shape2D};
@@ -119,7 +118,7 @@ impl<L: Line> LineBuilder<L, HasEnd> {
 
     /// Returns a `Shape2D` formed by the line pixels
     pub fn shape(self) -> Shape2D {
-        Shape2D::Complex(self.end().collect_vec())
+        self.end().collect()
     }
 }
 
@@ -0,0 +1,11 @@
+mod gltf_scene;
+mod ply;
+mod stl;
+mod texture;
+mod wavefront;
+
+pub use gltf_scene::*;
+pub use ply::*;
+pub use stl::*;
+pub use texture::*;
+pub use wavefront::*;
@@ -0,0 +1,434 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use glam::{Mat4, Vec2, Vec3};
+use gltf::{buffer, camera, image, mesh::Mode, Gltf, Node};
+use palette::{LinSrgba, Srgba};
+
+use crate::{
+    camera::{Camera, Projection},
+    formats::{Texture, TextureError},
+    mesh::IndexedMesh,
+    renderer::{AttributeBuffer, Mesh3D, Rasterizer, Semantic, VertexAttribute, World},
+};
+
+/// Triangles of one glTF mesh primitive, with the node transforms applied.
+#[derive(Debug, Clone)]
+pub struct GltfMesh {
+    /// Name of the mesh, empty if it has none
+    pub name: String,
+    /// Texture coordinates are the first set of the primitive, tangents and the second set
+    /// are in `extra`. Colors are the vertex colors multiplied with the base color
+    /// of the material and converted from linear to sRGB, the texture is its base color texture
+    /// upside down to suit `Texture::sample`.
+    pub mesh: IndexedMesh,
+}
+
+impl Mesh3D for GltfMesh {
+    fn vertices(&self) -> &[Vec3] {
+        self.mesh.vertices()
+    }
+
+    fn indices(&self) -> &[(usize, usize, usize)] {
+        self.mesh.indices()
+    }
+
+    fn attributes<'a>(&'a self, attributes: &mut Vec<VertexAttribute<'a>>) {
+        self.mesh.attributes(attributes)
+    }
+
+    fn texture(&self) -> Option<&Texture> {
+        self.mesh.texture()
+    }
+}
+
+/// Meshes and cameras of the default scene of a glTF 2.0 file.
+#[derive(Debug, Clone)]
+pub struct GltfScene {
+    pub meshes: Vec<GltfMesh>,
+    /// Cameras in the order their nodes are visited
+    pub cameras: Vec<Camera>,
+}
+
+#[derive(Debug)]
+pub enum GltfLoadError {
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    /// The file is not valid glTF
+    Gltf(gltf::Error),
+    /// The file requires an extension that is not implemented
+    UnsupportedExtension(String),
+    /// Something the loader can not convert, like points or lines
+    Unsupported(String),
+    /// A buffer or image references data that is not there
+    MissingData(String),
+    Texture {
+        image: usize,
+        error: TextureError,
+    },
+}
+
+impl fmt::Display for GltfLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GltfLoadError::Io { path, error } => {
+                write!(f, "could not read {}: {error}", path.display())
+            }
+            GltfLoadError::Gltf(e) => write!(f, "invalid glTF: {e}"),
+            GltfLoadError::UnsupportedExtension(name) => {
+                write!(f, "required glTF extension {name} is not supported")
+            }
+            GltfLoadError::Unsupported(what) => write!(f, "unsupported glTF content: {what}"),
+            GltfLoadError::MissingData(what) => write!(f, "missing glTF data: {what}"),
+            GltfLoadError::Texture { image, error } => write!(f, "image {image}: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for GltfLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GltfLoadError::Io { error, .. } => Some(error),
+            GltfLoadError::Gltf(e) => Some(e),
+            GltfLoadError::Texture { error, .. } => Some(error),
+            GltfLoadError::UnsupportedExtension(_)
+            | GltfLoadError::Unsupported(_)
+            | GltfLoadError::MissingData(_) => None,
+        }
+    }
+}
+
+impl From<gltf::Error> for GltfLoadError {
+    fn from(e: gltf::Error) -> Self {
+        GltfLoadError::Gltf(e)
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, GltfLoadError> {
+    std::fs::read(path).map_err(|error| GltfLoadError::Io {
+        path: path.to_owned(),
+        error,
+    })
+}
+
+/// Contents of a buffer or image `uri`, either embedded as base64 or a file relative to `directory`.
+fn read_uri(uri: &str, directory: &Path) -> Result<Vec<u8>, GltfLoadError> {
+    match uri.strip_prefix("data:") {
+        Some(data) => {
+            let (_, encoded) = data
+                .split_once(";base64,")
+                .ok_or_else(|| GltfLoadError::Unsupported(format!("data URI {data}")))?;
+            decode_base64(encoded)
+                .ok_or_else(|| GltfLoadError::MissingData("invalid base64 data URI".to_owned()))
+        }
+        None => read(&directory.join(uri)),
+    }
+}
+
+fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    let value = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    };
+    let encoded = encoded.trim_end_matches('=').as_bytes();
+    let mut bytes = Vec::with_capacity(encoded.len() * 3 / 4);
+    for chunk in encoded.chunks(4) {
+        let mut bits = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            bits |= (value(*c)? as u32) << (18 - 6 * i);
+        }
+        let decoded = bits.to_be_bytes();
+        bytes.extend_from_slice(&decoded[1..chunk.len()]);
+    }
+    Some(bytes)
+}
+
+impl GltfScene {
+    /// Reads a `.gltf` or `.glb` file, external buffers and images are relative to it.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GltfLoadError> {
+        let path = path.as_ref();
+        let directory = path.parent().unwrap_or(Path::new(""));
+        Self::from_slice(&read(path)?, directory)
+    }
+
+    /// Reads glTF JSON or binary glTF from memory.
+    /// External buffers and images are looked up relative to `directory`.
+    pub fn from_slice(bytes: &[u8], directory: impl AsRef<Path>) -> Result<Self, GltfLoadError> {
+        let directory = directory.as_ref();
+        // Validation rejects unknown required extensions without naming them, so look first
+        let unvalidated = Gltf::from_slice_without_validation(bytes)?;
+        if let Some(name) = unvalidated.extensions_required().next() {
+            return Err(GltfLoadError::UnsupportedExtension(name.to_owned()));
+        }
+        let gltf = Gltf::from_slice(bytes)?;
+
+        let buffers = gltf
+            .buffers()
+            .map(|b| {
+                let data = match b.source() {
+                    buffer::Source::Bin => gltf.blob.clone().ok_or_else(|| {
+                        GltfLoadError::MissingData("binary chunk of the GLB".to_owned())
+                    })?,
+                    buffer::Source::Uri(uri) => read_uri(uri, directory)?,
+                };
+                if data.len() < b.length() {
+                    return Err(GltfLoadError::MissingData(format!(
+                        "buffer {} holds {} of {} bytes",
+                        b.index(),
+                        data.len(),
+                        b.length()
+                    )));
+                }
+                Ok(data)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let textures = gltf
+            .images()
+            .map(|i| {
+                let data = match i.source() {
+                    image::Source::View { view, .. } => {
+                        let start = view.offset();
+                        start
+                            .checked_add(view.length())
+                            .and_then(|end| buffers[view.buffer().index()].get(start..end))
+                            .ok_or_else(|| {
+                                GltfLoadError::MissingData(format!(
+                                    "buffer view {} runs past its buffer",
+                                    view.index()
+                                ))
+                            })?
+                            .to_vec()
+                    }
+                    image::Source::Uri { uri, .. } => read_uri(uri, directory)?,
+                };
+                let texture = Texture::decode(&data).map_err(|error| GltfLoadError::Texture {
+                    image: i.index(),
+                    error,
+                })?;
+                // glTF puts v = 0 at the top of the image, so flip it for `Texture::sample`
+                Ok(Arc::new(texture.flipped()))
+            })
+            .collect::<Result<Vec<_>, GltfLoadError>>()?;
+
+        let mut scene = Self {
+            meshes: vec![],
+            cameras: vec![],
+        };
+        let Some(default_scene) = gltf.default_scene().or_else(|| gltf.scenes().next()) else {
+            return Ok(scene);
+        };
+        for node in default_scene.nodes() {
+            scene.add_node(&node, Mat4::IDENTITY, &buffers, &textures)?;
+        }
+        Ok(scene)
+    }
+
+    fn add_node(
+        &mut self,
+        node: &Node,
+        parent: Mat4,
+        buffers: &[Vec<u8>],
+        textures: &[Arc<Texture>],
+    ) -> Result<(), GltfLoadError> {
+        let transform = parent * Mat4::from_cols_array_2d(&node.transform().matrix());
+        if let Some(mesh) = node.mesh() {
+            for primitive in mesh.primitives() {
+                self.meshes.push(GltfMesh {
+                    name: mesh.name().unwrap_or_default().to_owned(),
+                    ..convert_primitive(&primitive, transform, buffers, textures)?
+                });
+            }
+        }
+        if let Some(camera) = node.camera() {
+            self.cameras.push(convert_camera(&camera, transform));
+        }
+        for child in node.children() {
+            self.add_node(&child, transform, buffers, textures)?;
+        }
+        Ok(())
+    }
+
+    /// A world looking through the first camera of the scene, or a default camera if there is none.
+    pub fn into_world(self, renderer: Rasterizer) -> World<Rasterizer> {
+        World {
+            camera: self.cameras.first().copied().unwrap_or_default(),
+            renderer,
+            objects: self
+                .meshes
+                .into_iter()
+                .map(|m| Box::new(m) as Box<dyn Mesh3D>)
+                .collect(),
+        }
+    }
+}
+
+fn convert_primitive(
+    primitive: &gltf::Primitive,
+    transform: Mat4,
+    buffers: &[Vec<u8>],
+    textures: &[Arc<Texture>],
+) -> Result<GltfMesh, GltfLoadError> {
+    let reader = primitive.reader(|b| buffers.get(b.index()).map(|d| d.as_slice()));
+    let positions: Vec<Vec3> = reader
+        .read_positions()
+        .ok_or_else(|| GltfLoadError::MissingData("primitive without positions".to_owned()))?
+        .map(|p| transform.transform_point3(Vec3::from(p)))
+        .collect();
+    let n = positions.len();
+    let mirrored = transform.determinant() < 0f32;
+
+    // Normals are transformed by the inverse transpose to stay perpendicular under scaling
+    let normal_matrix = transform.inverse().transpose();
+    let normals: Vec<Vec3> = reader
+        .read_normals()
+        .map(|n| {
+            n.map(|n| {
+                normal_matrix
+                    .transform_vector3(Vec3::from(n))
+                    .normalize_or_zero()
+            })
+            .collect()
+        })
+        .unwrap_or_default();
+    let tex_coords: Vec<Vec2> = reader
+        .read_tex_coords(0)
+        .map(|t| t.into_f32().map(Vec2::from).collect())
+        .unwrap_or_default();
+
+    let mut extra = vec![];
+    if let Some(tangents) = reader.read_tangents() {
+        // Tangents lie in the surface, so they follow the transform itself.
+        // Mirroring flips the bitangent computed from them, which the sign undoes.
+        let sign = if mirrored { -1f32 } else { 1f32 };
+        let tangents = tangents
+            .map(|t| {
+                let direction = transform.transform_vector3(Vec3::new(t[0], t[1], t[2]));
+                direction.normalize_or_zero().extend(t[3] * sign)
+            })
+            .collect();
+        extra.push((Semantic::Tangent, AttributeBuffer::Vec4(tangents)));
+    }
+    if let Some(tex_coords) = reader.read_tex_coords(1) {
+        let tex_coords = tex_coords.into_f32().map(Vec2::from).collect();
+        extra.push((Semantic::TexCoord(1), AttributeBuffer::Vec2(tex_coords)));
+    }
+
+    let pbr = primitive.material().pbr_metallic_roughness();
+    let [r, g, b, a] = pbr.base_color_factor();
+    let base_color = LinSrgba::new(r, g, b, a);
+    let texture = pbr.base_color_texture().and_then(|info| {
+        (info.tex_coord() == 0)
+            .then(|| textures.get(info.texture().source().index()).cloned())
+            .flatten()
+    });
+    let vertex_colors: Option<Vec<[f32; 4]>> =
+        reader.read_colors(0).map(|c| c.into_rgba_f32().collect());
+    // Both are linear, while the colors of the mesh are sRGB like its texture
+    let colors = (0..n)
+        .map(|i| {
+            let color = match vertex_colors.as_ref().and_then(|c| c.get(i)) {
+                Some([r, g, b, a]) => base_color * LinSrgba::new(*r, *g, *b, *a),
+                None => base_color,
+            };
+            Srgba::from_linear(color)
+        })
+        .collect();
+
+    let indices: Vec<usize> = match reader.read_indices() {
+        Some(indices) => indices.into_u32().map(|i| i as usize).collect(),
+        None => (0..n).collect(),
+    };
+    if let Some(i) = indices.iter().find(|i| **i >= n) {
+        return Err(GltfLoadError::MissingData(format!(
+            "index {i} of a primitive with {n} vertices"
+        )));
+    }
+    let mut triangles: Vec<(usize, usize, usize)> = match primitive.mode() {
+        Mode::Triangles => indices
+            .chunks_exact(3)
+            .map(|t| (t[0], t[1], t[2]))
+            .collect(),
+        // Every other triangle of a strip is flipped to keep the winding
+        Mode::TriangleStrip => (2..indices.len())
+            .map(|i| match i % 2 {
+                0 => (indices[i - 2], indices[i - 1], indices[i]),
+                _ => (indices[i - 1], indices[i - 2], indices[i]),
+            })
+            .collect(),
+        Mode::TriangleFan => (2..indices.len())
+            .map(|i| (indices[0], indices[i - 1], indices[i]))
+            .collect(),
+        mode => {
+            return Err(GltfLoadError::Unsupported(format!(
+                "primitive mode {mode:?}"
+            )))
+        }
+    };
+
+    // Mirroring turns the winding around, so turn it back to keep the front faces
+    if mirrored {
+        for t in &mut triangles {
+            *t = (t.0, t.2, t.1);
+        }
+    }
+
+    Ok(GltfMesh {
+        name: String::new(),
+        mesh: IndexedMesh {
+            positions,
+            normals,
+            tex_coords,
+            colors,
+            triangles,
+            extra,
+            texture,
+        },
+    })
+}
+
+fn convert_camera(camera: &gltf::Camera, transform: Mat4) -> Camera {
+    let (_, rotation, translation) = transform.to_scale_rotation_translation();
+    let mut result = Camera {
+        position: translation,
+        ..Default::default()
+    };
+    // glTF cameras look along -z with y up, like ours
+    result.set_orientation(rotation);
+
+    match camera.projection() {
+        camera::Projection::Perspective(p) => {
+            let height = result.aperture.1;
+            if let Some(aspect_ratio) = p.aspect_ratio() {
+                let width = (height as f32 * aspect_ratio).round().clamp(1f32, 255f32);
+                result.aperture = (width as u8, height);
+            }
+            result.focal_length = height as f32 / 2f32 / (p.yfov() / 2f32).tan();
+            result.near = p.znear();
+            // Infinite projections are cut off far away
+            result.far = p.zfar().unwrap_or(p.znear() * 1e4f32);
+        }
+        camera::Projection::Orthographic(o) => {
+            let height = result.aperture.1;
+            let width = (height as f32 * o.xmag() / o.ymag())
+                .round()
+                .clamp(1f32, 255f32);
+            result.aperture = (width as u8, height);
+            result.projection = Projection::Orthographic {
+                height: 2f32 * o.ymag(),
+            };
+            result.near = o.znear();
+            result.far = o.zfar();
+        }
+    }
+    result
+}
@@ -0,0 +1,453 @@
+use std::{
+    fmt,
+    io::{Read, Write},
+};
+
+use glam::{Vec2, Vec3};
+use palette::Srgba;
+
+use crate::mesh::IndexedMesh;
+
+/// Encoding of the data following a PLY header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PlyFormat {
+    Ascii,
+    #[default]
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug)]
+pub enum PlyLoadError {
+    Io(std::io::Error),
+    /// The header is malformed, at the given line
+    Header {
+        line: usize,
+        message: String,
+    },
+    /// The data does not match the header
+    Data(String),
+}
+
+impl fmt::Display for PlyLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlyLoadError::Io(e) => write!(f, "could not read PLY: {e}"),
+            PlyLoadError::Header { line, message } => {
+                write!(f, "invalid PLY header at line {line}: {message}")
+            }
+            PlyLoadError::Data(message) => write!(f, "invalid PLY data: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for PlyLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PlyLoadError::Io(e) => Some(e),
+            PlyLoadError::Header { .. } | PlyLoadError::Data(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for PlyLoadError {
+    fn from(e: std::io::Error) -> Self {
+        PlyLoadError::Io(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "char" | "int8" => Scalar::I8,
+            "uchar" | "uint8" => Scalar::U8,
+            "short" | "int16" => Scalar::I16,
+            "ushort" | "uint16" => Scalar::U16,
+            "int" | "int32" => Scalar::I32,
+            "uint" | "uint32" => Scalar::U32,
+            "float" | "float32" => Scalar::F32,
+            "double" | "float64" => Scalar::F64,
+            _ => return None,
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        }
+    }
+
+    /// Scale that maps the range of integer colors onto 0..=1
+    fn color_scale(self) -> f64 {
+        match self {
+            Scalar::I8 => i8::MAX as f64,
+            Scalar::U8 => u8::MAX as f64,
+            Scalar::I16 => i16::MAX as f64,
+            Scalar::U16 => u16::MAX as f64,
+            Scalar::I32 => i32::MAX as f64,
+            Scalar::U32 => u32::MAX as f64,
+            Scalar::F32 | Scalar::F64 => 1f64,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Property {
+    Scalar(Scalar),
+    List { count: Scalar, item: Scalar },
+}
+
+#[derive(Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<(String, Property)>,
+}
+
+/// Source of the values of the data section, in the order of the header.
+enum Values<'a> {
+    Ascii(std::str::SplitAsciiWhitespace<'a>),
+    Binary { bytes: &'a [u8], big_endian: bool },
+}
+
+impl Values<'_> {
+    /// Next value as a count or index, which must be a whole number that is not negative.
+    fn next_index(&mut self, scalar: Scalar) -> Result<usize, PlyLoadError> {
+        let value = self.next(scalar)?;
+        if value < 0f64 || value.fract() != 0f64 {
+            return Err(PlyLoadError::Data(format!(
+                "{value} is not a valid count or index"
+            )));
+        }
+        Ok(value as usize)
+    }
+
+    fn next(&mut self, scalar: Scalar) -> Result<f64, PlyLoadError> {
+        let eof = || PlyLoadError::Data("file ends before all elements were read".to_owned());
+        match self {
+            Values::Ascii(words) => {
+                let word = words.next().ok_or_else(eof)?;
+                word.parse()
+                    .map_err(|_| PlyLoadError::Data(format!("`{word}` is not a number")))
+            }
+            Values::Binary { bytes, big_endian } => {
+                let size = scalar.size();
+                if bytes.len() < size {
+                    return Err(eof());
+                }
+                let (value, rest) = bytes.split_at(size);
+                *bytes = rest;
+                let mut raw = [0u8; 8];
+                raw[..size].copy_from_slice(value);
+                if *big_endian {
+                    raw[..size].reverse();
+                }
+                Ok(match scalar {
+                    Scalar::I8 => raw[0] as i8 as f64,
+                    Scalar::U8 => raw[0] as f64,
+                    Scalar::I16 => i16::from_le_bytes([raw[0], raw[1]]) as f64,
+                    Scalar::U16 => u16::from_le_bytes([raw[0], raw[1]]) as f64,
+                    Scalar::I32 => i32::from_le_bytes(raw[..4].try_into().unwrap()) as f64,
+                    Scalar::U32 => u32::from_le_bytes(raw[..4].try_into().unwrap()) as f64,
+                    Scalar::F32 => f32::from_le_bytes(raw[..4].try_into().unwrap()) as f64,
+                    Scalar::F64 => f64::from_le_bytes(raw),
+                })
+            }
+        }
+    }
+}
+
+/// Reads an ASCII or binary PLY file.
+///
+/// The `vertex` element gives the positions (`x y z`) and optionally normals (`nx ny nz`),
+/// colors (`red green blue [alpha]`) and texture coordinates (`u v`, `s t` or
+/// `texture_u texture_v`). Polygons of the `face` element are triangulated as fans,
+/// other elements are skipped.
+pub fn read_ply(mut input: impl Read) -> Result<IndexedMesh, PlyLoadError> {
+    let mut bytes = vec![];
+    input.read_to_end(&mut bytes)?;
+
+    let (format, elements, data) = read_header(&bytes)?;
+    let text;
+    let mut values = match format {
+        PlyFormat::Ascii => {
+            text = String::from_utf8_lossy(data);
+            Values::Ascii(text.split_ascii_whitespace())
+        }
+        PlyFormat::BinaryLittleEndian | PlyFormat::BinaryBigEndian => Values::Binary {
+            bytes: data,
+            big_endian: format == PlyFormat::BinaryBigEndian,
+        },
+    };
+
+    let mut mesh = IndexedMesh::default();
+    let mut faces = vec![];
+    for element in &elements {
+        let find = |names: &[&str]| {
+            names.iter().find_map(|name| {
+                element
+                    .properties
+                    .iter()
+                    .position(|(n, p)| n == name && matches!(p, Property::Scalar(_)))
+            })
+        };
+        let all = |names: &[&[&str]]| names.iter().map(|n| find(n)).collect::<Option<Vec<_>>>();
+        let position = all(&[&["x"], &["y"], &["z"]]);
+        let normal = all(&[&["nx"], &["ny"], &["nz"]]);
+        let color = all(&[&["red"], &["green"], &["blue"]]);
+        let alpha = find(&["alpha"]);
+        let tex_coord = all(&[&["u", "s", "texture_u"], &["v", "t", "texture_v"]]);
+        let indices = element.properties.iter().position(|(n, p)| {
+            (n == "vertex_indices" || n == "vertex_index") && matches!(p, Property::List { .. })
+        });
+        if element.name == "vertex" && position.is_none() {
+            return Err(PlyLoadError::Data("vertices have no x, y and z".to_owned()));
+        }
+
+        let mut row = vec![0f64; element.properties.len()];
+        let mut list = vec![];
+        for _ in 0..element.count {
+            for (i, (_, property)) in element.properties.iter().enumerate() {
+                match *property {
+                    Property::Scalar(scalar) => row[i] = values.next(scalar)?,
+                    Property::List { count, item } => {
+                        let count = values.next_index(count)?;
+                        let keep = element.name == "face" && Some(i) == indices;
+                        if keep {
+                            list.clear();
+                            for _ in 0..count {
+                                list.push(values.next_index(item)?);
+                            }
+                        } else {
+                            for _ in 0..count {
+                                values.next(item)?;
+                            }
+                        }
+                    }
+                }
+            }
+
+            match element.name.as_str() {
+                "vertex" => {
+                    let vec3 = |p: &Vec<usize>| {
+                        Vec3::new(row[p[0]] as f32, row[p[1]] as f32, row[p[2]] as f32)
+                    };
+                    mesh.positions.push(vec3(position.as_ref().unwrap()));
+                    if let Some(normal) = &normal {
+                        mesh.normals.push(vec3(normal));
+                    }
+                    if let Some(tex_coord) = &tex_coord {
+                        mesh.tex_coords.push(Vec2::new(
+                            row[tex_coord[0]] as f32,
+                            row[tex_coord[1]] as f32,
+                        ));
+                    }
+                    if let Some(color) = &color {
+                        let channel = |i: usize| {
+                            let Property::Scalar(scalar) = element.properties[i].1 else {
+                                unreachable!()
+                            };
+                            (row[i] / scalar.color_scale()) as f32
+                        };
+                        mesh.colors.push(Srgba::new(
+                            channel(color[0]),
+                            channel(color[1]),
+                            channel(color[2]),
+                            alpha.map_or(1f32, channel),
+                        ));
+                    }
+                }
+                "face" if indices.is_some() => {
+                    for i in 1..list.len().saturating_sub(1) {
+                        faces.push((list[0], list[i], list[i + 1]));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let count = mesh.positions.len();
+    if let Some(t) = faces.iter().find(|t| t.0.max(t.1).max(t.2) >= count) {
+        return Err(PlyLoadError::Data(format!(
+            "face {t:?} refers to a missing vertex, there are {count}"
+        )));
+    }
+    mesh.triangles = faces;
+    Ok(mesh)
+}
+
+fn read_header(bytes: &[u8]) -> Result<(PlyFormat, Vec<Element>, &[u8]), PlyLoadError> {
+    let mut format = None;
+    let mut elements: Vec<Element> = vec![];
+    let mut rest = bytes;
+
+    for line_number in 1.. {
+        let error = |message: &str| PlyLoadError::Header {
+            line: line_number,
+            message: message.to_owned(),
+        };
+        let end = rest
+            .iter()
+            .position(|b| *b == b'\n')
+            .ok_or_else(|| error("missing `end_header`"))?;
+        let line = String::from_utf8_lossy(&rest[..end]);
+        rest = &rest[end + 1..];
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        if line_number == 1 {
+            if words != ["ply"] {
+                return Err(error("not a PLY file"));
+            }
+            continue;
+        }
+        match words[..] {
+            ["format", name, version] => {
+                if version != "1.0" {
+                    return Err(error(&format!("version {version} is not supported")));
+                }
+                format = Some(match name {
+                    "ascii" => PlyFormat::Ascii,
+                    "binary_little_endian" => PlyFormat::BinaryLittleEndian,
+                    "binary_big_endian" => PlyFormat::BinaryBigEndian,
+                    _ => return Err(error(&format!("unknown format `{name}`"))),
+                });
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_owned(),
+                count: count.parse().map_err(|_| error("invalid element count"))?,
+                properties: vec![],
+            }),
+            ["property", ..] => {
+                let element = elements
+                    .last_mut()
+                    .ok_or_else(|| error("property before any element"))?;
+                let scalar = |name: &str| {
+                    Scalar::parse(name).ok_or_else(|| error(&format!("unknown type `{name}`")))
+                };
+                let property = match words[1..] {
+                    ["list", count, item, name] => (
+                        name,
+                        Property::List {
+                            count: scalar(count)?,
+                            item: scalar(item)?,
+                        },
+                    ),
+                    [kind, name] => (name, Property::Scalar(scalar(kind)?)),
+                    _ => return Err(error("invalid property")),
+                };
+                element.properties.push((property.0.to_owned(), property.1));
+            }
+            ["comment" | "obj_info", ..] | [] => {}
+            ["end_header"] => {
+                let format = format.ok_or_else(|| error("missing format"))?;
+                return Ok((format, elements, rest));
+            }
+            _ => return Err(error(&format!("unexpected `{}`", line.trim()))),
+        }
+    }
+    unreachable!()
+}
+
+/// Writes `mesh` as PLY, with the normals, texture coordinates and colors it has.
+///
+/// Colors are stored as `uchar`, everything else as `float`.
+pub fn write_ply(
+    mesh: &IndexedMesh,
+    format: PlyFormat,
+    mut output: impl Write,
+) -> std::io::Result<()> {
+    let name = match format {
+        PlyFormat::Ascii => "ascii",
+        PlyFormat::BinaryLittleEndian => "binary_little_endian",
+        PlyFormat::BinaryBigEndian => "binary_big_endian",
+    };
+    writeln!(output, "ply")?;
+    writeln!(output, "format {name} 1.0")?;
+    writeln!(output, "comment pixel-renderer")?;
+    writeln!(output, "element vertex {}", mesh.positions.len())?;
+    let mut floats = vec!["x", "y", "z"];
+    if !mesh.normals.is_empty() {
+        floats.extend(["nx", "ny", "nz"]);
+    }
+    if !mesh.tex_coords.is_empty() {
+        floats.extend(["u", "v"]);
+    }
+    for name in &floats {
+        writeln!(output, "property float {name}")?;
+    }
+    if !mesh.colors.is_empty() {
+        for name in ["red", "green", "blue", "alpha"] {
+            writeln!(output, "property uchar {name}")?;
+        }
+    }
+    writeln!(output, "element face {}", mesh.triangles.len())?;
+    writeln!(output, "property list uchar uint vertex_indices")?;
+    writeln!(output, "end_header")?;
+
+    for i in 0..mesh.positions.len() {
+        let mut vertex = mesh.positions[i].to_array().to_vec();
+        if let Some(n) = mesh.normals.get(i) {
+            vertex.extend(n.to_array());
+        }
+        if let Some(uv) = mesh.tex_coords.get(i) {
+            vertex.extend(uv.to_array());
+        }
+        let color: Option<[u8; 4]> = mesh.colors.get(i).map(|c| c.into_format().into());
+
+        match format {
+            PlyFormat::Ascii => {
+                let mut line: Vec<String> = vertex.iter().map(|v| v.to_string()).collect();
+                line.extend(color.iter().flatten().map(|c| c.to_string()));
+                writeln!(output, "{}", line.join(" "))?;
+            }
+            PlyFormat::BinaryLittleEndian => {
+                for v in vertex {
+                    output.write_all(&v.to_le_bytes())?;
+                }
+                output.write_all(color.as_ref().map_or(&[][..], |c| &c[..]))?;
+            }
+            PlyFormat::BinaryBigEndian => {
+                for v in vertex {
+                    output.write_all(&v.to_be_bytes())?;
+                }
+                output.write_all(color.as_ref().map_or(&[][..], |c| &c[..]))?;
+            }
+        }
+    }
+
+    for t in &mesh.triangles {
+        let indices = [t.0 as u32, t.1 as u32, t.2 as u32];
+        match format {
+            PlyFormat::Ascii => writeln!(output, "3 {} {} {}", t.0, t.1, t.2)?,
+            PlyFormat::BinaryLittleEndian => {
+                output.write_all(&[3])?;
+                for i in indices {
+                    output.write_all(&i.to_le_bytes())?;
+                }
+            }
+            PlyFormat::BinaryBigEndian => {
+                output.write_all(&[3])?;
+                for i in indices {
+                    output.write_all(&i.to_be_bytes())?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,206 @@
+use std::{
+    fmt,
+    io::{Read, Write},
+};
+
+use glam::Vec3;
+
+use crate::mesh::IndexedMesh;
+
+/// Encoding of an STL file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StlFormat {
+    /// Human readable `solid ... endsolid`
+    Ascii,
+    /// 80 byte header, triangle count and 50 bytes per triangle
+    #[default]
+    Binary,
+}
+
+#[derive(Debug)]
+pub enum StlLoadError {
+    Io(std::io::Error),
+    /// The file ends before the triangle count says it should
+    Truncated,
+    /// A statement of an ASCII file is not what was expected, at the given line
+    Syntax {
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for StlLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StlLoadError::Io(e) => write!(f, "could not read STL: {e}"),
+            StlLoadError::Truncated => write!(f, "binary STL is shorter than its triangle count"),
+            StlLoadError::Syntax { line, message } => {
+                write!(f, "invalid ASCII STL at line {line}: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StlLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StlLoadError::Io(e) => Some(e),
+            StlLoadError::Truncated | StlLoadError::Syntax { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for StlLoadError {
+    fn from(e: std::io::Error) -> Self {
+        StlLoadError::Io(e)
+    }
+}
+
+/// Reads an ASCII or binary STL file.
+///
+/// STL has no shared vertices, every triangle gets three vertices with its facet normal.
+/// Facet normals that are missing or zero are computed from the winding.
+pub fn read_stl(mut input: impl Read) -> Result<IndexedMesh, StlLoadError> {
+    let mut bytes = vec![];
+    input.read_to_end(&mut bytes)?;
+
+    // Binary files may start with "solid" too, so the size and `endsolid` decide
+    let binary_size = bytes
+        .get(80..84)
+        .map(|count| 84 + 50 * u32::from_le_bytes(count.try_into().unwrap()) as usize);
+    let ascii = binary_size != Some(bytes.len())
+        && bytes.trim_ascii_start().starts_with(b"solid")
+        && bytes.windows(8).any(|w| w == b"endsolid");
+    let mut mesh = if ascii {
+        read_ascii(&String::from_utf8_lossy(&bytes))?
+    } else {
+        read_binary(&bytes)?
+    };
+
+    for t in 0..mesh.triangles.len() {
+        let i = 3 * t;
+        if mesh.normals[i] == Vec3::ZERO || !mesh.normals[i].is_finite() {
+            let normal = mesh.face_normal(mesh.triangles[t]);
+            mesh.normals[i..i + 3].fill(normal);
+        }
+    }
+    Ok(mesh)
+}
+
+fn push_facet(mesh: &mut IndexedMesh, normal: Vec3, vertices: [Vec3; 3]) {
+    let i = mesh.positions.len();
+    mesh.positions.extend(vertices);
+    mesh.normals.extend([normal; 3]);
+    mesh.triangles.push((i, i + 1, i + 2));
+}
+
+fn read_binary(bytes: &[u8]) -> Result<IndexedMesh, StlLoadError> {
+    let count = bytes.get(80..84).ok_or(StlLoadError::Truncated)?;
+    let count = u32::from_le_bytes(count.try_into().unwrap()) as usize;
+    let facets = &bytes[84..];
+    if facets.len() < count * 50 {
+        return Err(StlLoadError::Truncated);
+    }
+
+    let mut mesh = IndexedMesh::default();
+    for facet in facets.chunks_exact(50).take(count) {
+        let v = |i: usize| {
+            let f = |j: usize| {
+                let at = 12 * i + 4 * j;
+                f32::from_le_bytes(facet[at..at + 4].try_into().unwrap())
+            };
+            Vec3::new(f(0), f(1), f(2))
+        };
+        // The attribute byte count at the end is ignored
+        push_facet(&mut mesh, v(0), [v(1), v(2), v(3)]);
+    }
+    Ok(mesh)
+}
+
+fn read_ascii(text: &str) -> Result<IndexedMesh, StlLoadError> {
+    let mut mesh = IndexedMesh::default();
+    let mut normal = Vec3::ZERO;
+    let mut vertices = vec![];
+
+    for (i, line) in text.lines().enumerate() {
+        let syntax = |message: &str| StlLoadError::Syntax {
+            line: i + 1,
+            message: message.to_owned(),
+        };
+        let mut words = line.split_whitespace();
+        let vector = |words: std::str::SplitWhitespace| {
+            let values: Vec<f32> = words
+                .map(|w| w.parse::<f32>())
+                .collect::<Result<_, _>>()
+                .map_err(|_| syntax("expected numbers"))?;
+            match values[..] {
+                [x, y, z] => Ok(Vec3::new(x, y, z)),
+                _ => Err(syntax("expected 3 numbers")),
+            }
+        };
+
+        match words.next() {
+            Some("facet") => {
+                if words.next() != Some("normal") {
+                    return Err(syntax("expected `facet normal`"));
+                }
+                normal = vector(words)?;
+                vertices.clear();
+            }
+            Some("vertex") => vertices.push(vector(words)?),
+            Some("endfacet") => match vertices[..] {
+                [a, b, c] => push_facet(&mut mesh, normal, [a, b, c]),
+                _ => return Err(syntax("facets need exactly 3 vertices")),
+            },
+            Some("solid" | "outer" | "endloop" | "endsolid") | None => {}
+            Some(word) => return Err(syntax(&format!("unexpected `{word}`"))),
+        }
+    }
+    Ok(mesh)
+}
+
+/// Writes `mesh` as STL with facet normals computed from the winding of its triangles.
+pub fn write_stl(
+    mesh: &IndexedMesh,
+    format: StlFormat,
+    mut output: impl Write,
+) -> std::io::Result<()> {
+    match format {
+        StlFormat::Ascii => {
+            writeln!(output, "solid mesh")?;
+            for t in &mesh.triangles {
+                let n = mesh.face_normal(*t);
+                writeln!(output, "  facet normal {:e} {:e} {:e}", n.x, n.y, n.z)?;
+                writeln!(output, "    outer loop")?;
+                for i in [t.0, t.1, t.2] {
+                    let v = mesh.positions[i];
+                    writeln!(output, "      vertex {:e} {:e} {:e}", v.x, v.y, v.z)?;
+                }
+                writeln!(output, "    endloop")?;
+                writeln!(output, "  endfacet")?;
+            }
+            writeln!(output, "endsolid mesh")?;
+        }
+        StlFormat::Binary => {
+            let mut header = [0u8; 80];
+            header[..15].copy_from_slice(b"pixel-renderer ");
+            output.write_all(&header)?;
+            output.write_all(&(mesh.triangles.len() as u32).to_le_bytes())?;
+            for t in &mesh.triangles {
+                let n = mesh.face_normal(*t);
+                let vertices = [
+                    mesh.positions[t.0],
+                    mesh.positions[t.1],
+                    mesh.positions[t.2],
+                ];
+                for v in [n].iter().chain(&vertices) {
+                    for c in v.to_array() {
+                        output.write_all(&c.to_le_bytes())?;
+                    }
+                }
+                output.write_all(&0u16.to_le_bytes())?;
+            }
+        }
+    }
+    Ok(())
+}
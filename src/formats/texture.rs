@@ -0,0 +1,142 @@
+use std::{fmt, io::Cursor, path::Path};
+
+use glam::Vec2;
+use palette::Srgba;
+
+/// An RGBA image sampled by texture coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Texture {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub enum TextureError {
+    Io(std::io::Error),
+    Png(png::DecodingError),
+    /// The image is not a PNG
+    UnsupportedFormat,
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureError::Io(e) => write!(f, "could not read texture: {e}"),
+            TextureError::Png(e) => write!(f, "could not decode PNG texture: {e}"),
+            TextureError::UnsupportedFormat => write!(f, "only PNG textures are supported"),
+        }
+    }
+}
+
+impl std::error::Error for TextureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TextureError::Io(e) => Some(e),
+            TextureError::Png(e) => Some(e),
+            TextureError::UnsupportedFormat => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for TextureError {
+    fn from(e: std::io::Error) -> Self {
+        TextureError::Io(e)
+    }
+}
+
+impl From<png::DecodingError> for TextureError {
+    fn from(e: png::DecodingError) -> Self {
+        TextureError::Png(e)
+    }
+}
+
+impl Texture {
+    /// Returns `None` if `data` does not hold exactly `width * height` pixels.
+    pub fn new(width: u32, height: u32, data: Vec<u8>) -> Option<Self> {
+        (width > 0 && height > 0 && data.len() == width as usize * height as usize * 4).then_some(
+            Self {
+                width,
+                height,
+                data,
+            },
+        )
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// 8 bit RGBA pixels row by row, starting at the top.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// The same image upside down.
+    pub(crate) fn flipped(&self) -> Self {
+        let row = self.width as usize * 4;
+        Self {
+            data: self
+                .data
+                .chunks_exact(row)
+                .rev()
+                .flatten()
+                .copied()
+                .collect(),
+            ..*self
+        }
+    }
+
+    /// Reads an image file, only PNG is supported.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TextureError> {
+        Self::decode(&std::fs::read(path)?)
+    }
+
+    /// Decodes an image held in memory, only PNG is supported.
+    pub fn decode(bytes: &[u8]) -> Result<Self, TextureError> {
+        const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+        if !bytes.starts_with(&PNG_SIGNATURE) {
+            return Err(TextureError::UnsupportedFormat);
+        }
+
+        let mut decoder = png::Decoder::new(Cursor::new(bytes));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size().unwrap_or_default()];
+        let info = reader.next_frame(&mut buffer)?;
+        buffer.truncate(info.buffer_size());
+
+        let data = match info.color_type {
+            png::ColorType::Rgba => buffer,
+            png::ColorType::Rgb => buffer
+                .chunks_exact(3)
+                .flat_map(|p| [p[0], p[1], p[2], 0xff])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buffer
+                .chunks_exact(2)
+                .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            png::ColorType::Grayscale => buffer.iter().flat_map(|g| [*g, *g, *g, 0xff]).collect(),
+            // Palettes are expanded by the transformations
+            png::ColorType::Indexed => return Err(TextureError::UnsupportedFormat),
+        };
+        Self::new(info.width, info.height, data).ok_or(TextureError::UnsupportedFormat)
+    }
+
+    /// Color at texture coordinates `uv`, with v going up from the bottom of the image.
+    /// Coordinates outside of [0; 1] repeat the image.
+    pub fn sample(&self, uv: Vec2) -> Srgba {
+        // Nearest neighbour filtering
+        let x = (uv.x.rem_euclid(1f32) * self.width as f32) as usize;
+        let y = ((1f32 - uv.y).rem_euclid(1f32) * self.height as f32) as usize;
+        let idx = (y.min(self.height as usize - 1) * self.width as usize
+            + x.min(self.width as usize - 1))
+            * 4;
+        let p = &self.data[idx..idx + 4];
+        Srgba::new(p[0], p[1], p[2], p[3]).into_format()
+    }
+}
@@ -0,0 +1,370 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use glam::{Vec2, Vec3};
+use obj::raw::{
+    material::{Material as RawMaterial, MtlColor},
+    object::{Group, Polygon},
+    parse_mtl, parse_obj, RawObj,
+};
+use palette::{FromColor, Srgb, Srgba, Xyz};
+
+use crate::{
+    formats::{Texture, TextureError},
+    mesh::IndexedMesh,
+    renderer::{Mesh3D, VertexAttribute},
+};
+
+/// Surface description read from a `.mtl` material library.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    pub name: String,
+    /// Diffuse color `Kd`
+    pub diffuse: Srgb,
+    /// Specular color `Ks`
+    pub specular: Srgb,
+    /// Specular exponent `Ns`
+    pub shininess: f32,
+    /// Dissolve `d`, 1 is fully opaque
+    pub opacity: f32,
+    /// Diffuse texture `map_Kd`, multiplied with `diffuse`
+    pub diffuse_map: Option<Arc<Texture>>,
+}
+
+impl Default for Material {
+    /// Opaque white, used for faces without a material
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            diffuse: Srgb::new(1f32, 1f32, 1f32),
+            specular: Srgb::new(0f32, 0f32, 0f32),
+            shininess: 0f32,
+            opacity: 1f32,
+            diffuse_map: None,
+        }
+    }
+}
+
+/// Faces of one group of an OBJ file that share a material.
+#[derive(Debug, Clone)]
+pub struct ObjMesh {
+    /// Name of the group, `default` for faces outside of any group
+    pub name: String,
+    /// Index in `ObjModel::materials`
+    pub material: usize,
+    /// Normals and texture coordinates are zero for vertices of faces without them.
+    /// Colors are the diffuse color and opacity of the material, the texture is its diffuse map.
+    pub mesh: IndexedMesh,
+}
+
+impl Mesh3D for ObjMesh {
+    fn vertices(&self) -> &[Vec3] {
+        self.mesh.vertices()
+    }
+
+    fn indices(&self) -> &[(usize, usize, usize)] {
+        self.mesh.indices()
+    }
+
+    fn attributes<'a>(&'a self, attributes: &mut Vec<VertexAttribute<'a>>) {
+        self.mesh.attributes(attributes)
+    }
+
+    fn texture(&self) -> Option<&Texture> {
+        self.mesh.texture()
+    }
+}
+
+/// A Wavefront OBJ file with its materials.
+#[derive(Debug, Clone)]
+pub struct ObjModel {
+    /// Name given by the `o` statement
+    pub name: Option<String>,
+    /// One mesh for every material used by every group, in the order they appear in the file
+    pub meshes: Vec<ObjMesh>,
+    /// Materials used by the meshes, the first one is the default for faces without a material
+    pub materials: Vec<Material>,
+}
+
+#[derive(Debug)]
+pub enum ObjLoadError {
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    Parse(obj::ObjError),
+    /// A face uses a material that none of the material libraries define
+    MissingMaterial(String),
+    /// The material library uses something that can not be converted
+    Unsupported(String),
+    Texture {
+        path: PathBuf,
+        error: TextureError,
+    },
+}
+
+impl fmt::Display for ObjLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjLoadError::Io { path, error } => {
+                write!(f, "could not read {}: {error}", path.display())
+            }
+            ObjLoadError::Parse(e) => write!(f, "could not parse OBJ: {e}"),
+            ObjLoadError::MissingMaterial(name) => write!(f, "material {name} is not defined"),
+            ObjLoadError::Unsupported(what) => write!(f, "unsupported material: {what}"),
+            ObjLoadError::Texture { path, error } => write!(f, "{}: {error}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ObjLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ObjLoadError::Io { error, .. } => Some(error),
+            ObjLoadError::Parse(e) => Some(e),
+            ObjLoadError::Texture { error, .. } => Some(error),
+            ObjLoadError::MissingMaterial(_) | ObjLoadError::Unsupported(_) => None,
+        }
+    }
+}
+
+impl From<obj::ObjError> for ObjLoadError {
+    fn from(e: obj::ObjError) -> Self {
+        ObjLoadError::Parse(e)
+    }
+}
+
+fn open(path: &Path) -> Result<BufReader<File>, ObjLoadError> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|error| ObjLoadError::Io {
+            path: path.to_owned(),
+            error,
+        })
+}
+
+impl ObjModel {
+    /// Reads the OBJ file at `path` together with the material libraries it references.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ObjLoadError> {
+        let path = path.as_ref();
+        let directory = path.parent().unwrap_or(Path::new(""));
+        Self::from_reader(open(path)?, directory)
+    }
+
+    /// Reads an OBJ file from `input`.
+    /// Material libraries and their textures are looked up relative to `directory`.
+    pub fn from_reader(
+        input: impl BufRead,
+        directory: impl AsRef<Path>,
+    ) -> Result<Self, ObjLoadError> {
+        let directory = directory.as_ref();
+        let raw = parse_obj(input)?;
+
+        let mut library = HashMap::new();
+        for name in &raw.material_libraries {
+            let path = directory.join(name);
+            let mtl = parse_mtl(open(&path)?)?;
+            // Textures are relative to the library they are referenced from
+            let library_directory = path.parent().unwrap_or(directory);
+            for (name, material) in mtl.materials {
+                let material = convert_material(&name, &material, library_directory)?;
+                library.insert(name, material);
+            }
+        }
+
+        let group_names = face_owners(&raw.groups, raw.polygons.len());
+        let material_names = face_owners(&raw.meshes, raw.polygons.len());
+
+        let mut materials = vec![Material::default()];
+        let mut material_indices: HashMap<&str, usize> = HashMap::new();
+        let mut meshes: Vec<MeshBuilder> = vec![];
+        let mut mesh_indices: HashMap<(&str, usize), usize> = HashMap::new();
+        for (face, polygon) in raw.polygons.iter().enumerate() {
+            let material = match material_names[face] {
+                None | Some("") => 0,
+                Some(name) => match material_indices.get(name) {
+                    Some(i) => *i,
+                    None => {
+                        let material = library
+                            .get(name)
+                            .ok_or_else(|| ObjLoadError::MissingMaterial(name.to_owned()))?;
+                        materials.push(material.clone());
+                        material_indices.insert(name, materials.len() - 1);
+                        materials.len() - 1
+                    }
+                },
+            };
+            let group = group_names[face].unwrap_or("default");
+            let mesh = *mesh_indices.entry((group, material)).or_insert_with(|| {
+                meshes.push(MeshBuilder::new(group, material, &materials[material]));
+                meshes.len() - 1
+            });
+            meshes[mesh].add(polygon, &raw, &materials[material]);
+        }
+
+        Ok(Self {
+            name: raw.name.clone(),
+            meshes: meshes.into_iter().map(MeshBuilder::finish).collect(),
+            materials,
+        })
+    }
+
+    /// Every mesh as something the `Rasterizer` can draw.
+    pub fn into_renderables(self) -> Vec<Box<dyn Mesh3D>> {
+        self.meshes
+            .into_iter()
+            .map(|m| Box::new(m) as Box<dyn Mesh3D>)
+            .collect()
+    }
+}
+
+/// Name of the group every face belongs to.
+/// Faces in several groups, like after `g a b`, belong to the first of them by name.
+fn face_owners(groups: &HashMap<String, Group>, faces: usize) -> Vec<Option<&str>> {
+    let mut groups: Vec<_> = groups.iter().collect();
+    groups.sort_unstable_by_key(|(name, _)| name.as_str());
+    let mut owners = vec![None; faces];
+    for (name, group) in groups {
+        for range in &group.polygons {
+            for owner in &mut owners[range.start..range.end.min(faces)] {
+                owner.get_or_insert(name.as_str());
+            }
+        }
+    }
+    owners
+}
+
+fn convert_color(color: &MtlColor) -> Result<Srgb, ObjLoadError> {
+    match color {
+        MtlColor::Rgb(r, g, b) => Ok(Srgb::new(*r, *g, *b)),
+        MtlColor::Xyz(x, y, z) => Ok(Srgb::from_color(Xyz::new(*x, *y, *z))),
+        MtlColor::Spectral(file, _) => Err(ObjLoadError::Unsupported(format!(
+            "spectral color from {file}"
+        ))),
+    }
+}
+
+fn convert_material(
+    name: &str,
+    material: &RawMaterial,
+    directory: &Path,
+) -> Result<Material, ObjLoadError> {
+    let default = Material::default();
+    let diffuse_map = match &material.diffuse_map {
+        Some(map) => {
+            let path = directory.join(&map.file);
+            let texture =
+                Texture::load(&path).map_err(|error| ObjLoadError::Texture { path, error })?;
+            Some(Arc::new(texture))
+        }
+        None => None,
+    };
+    Ok(Material {
+        name: name.to_owned(),
+        diffuse: match &material.diffuse {
+            Some(c) => convert_color(c)?,
+            None => default.diffuse,
+        },
+        specular: match &material.specular {
+            Some(c) => convert_color(c)?,
+            None => default.specular,
+        },
+        shininess: material.specular_exponent.unwrap_or(default.shininess),
+        opacity: material.dissolve.unwrap_or(default.opacity),
+        diffuse_map,
+    })
+}
+
+/// Collects the faces of one mesh, sharing vertices that are the same in every attribute.
+struct MeshBuilder {
+    mesh: ObjMesh,
+    /// Index in the mesh of every combination of position, texture coordinate and normal
+    vertices: HashMap<(usize, Option<usize>, Option<usize>), usize>,
+    has_normals: bool,
+    has_tex_coords: bool,
+}
+
+impl MeshBuilder {
+    fn new(name: &str, material_index: usize, material: &Material) -> Self {
+        Self {
+            mesh: ObjMesh {
+                name: name.to_owned(),
+                material: material_index,
+                mesh: IndexedMesh {
+                    texture: material.diffuse_map.clone(),
+                    ..Default::default()
+                },
+            },
+            vertices: HashMap::new(),
+            has_normals: false,
+            has_tex_coords: false,
+        }
+    }
+
+    fn finish(mut self) -> ObjMesh {
+        if !self.has_normals {
+            self.mesh.mesh.normals.clear();
+        }
+        if !self.has_tex_coords {
+            self.mesh.mesh.tex_coords.clear();
+        }
+        self.mesh
+    }
+
+    fn vertex(
+        &mut self,
+        key: (usize, Option<usize>, Option<usize>),
+        raw: &RawObj,
+        material: &Material,
+    ) -> usize {
+        if let Some(i) = self.vertices.get(&key) {
+            return *i;
+        }
+        let mesh = &mut self.mesh.mesh;
+        let (position, tex_coord, normal) = key;
+        let (x, y, z, w) = raw.positions[position];
+        mesh.positions.push(Vec3::new(x, y, z) / w);
+
+        let tex_coord = tex_coord.map(|t| Vec2::new(raw.tex_coords[t].0, raw.tex_coords[t].1));
+        mesh.tex_coords.push(tex_coord.unwrap_or(Vec2::ZERO));
+        let normal =
+            normal.map(|n| Vec3::new(raw.normals[n].0, raw.normals[n].1, raw.normals[n].2));
+        mesh.normals.push(normal.unwrap_or(Vec3::ZERO));
+        self.has_tex_coords |= tex_coord.is_some();
+        self.has_normals |= normal.is_some();
+
+        let d = material.diffuse;
+        mesh.colors
+            .push(Srgba::new(d.red, d.green, d.blue, material.opacity));
+
+        let i = mesh.positions.len() - 1;
+        self.vertices.insert(key, i);
+        i
+    }
+
+    /// Adds `polygon` as a fan of triangles.
+    fn add(&mut self, polygon: &Polygon, raw: &RawObj, material: &Material) {
+        let keys: Vec<_> = match polygon {
+            Polygon::P(p) => p.iter().map(|p| (*p, None, None)).collect(),
+            Polygon::PT(p) => p.iter().map(|(p, t)| (*p, Some(*t), None)).collect(),
+            Polygon::PN(p) => p.iter().map(|(p, n)| (*p, None, Some(*n))).collect(),
+            Polygon::PTN(p) => p.iter().map(|(p, t, n)| (*p, Some(*t), Some(*n))).collect(),
+        };
+        let indices: Vec<_> = keys
+            .into_iter()
+            .map(|k| self.vertex(k, raw, material))
+            .collect();
+        for i in 1..indices.len().saturating_sub(1) {
+            self.mesh
+                .mesh
+                .triangles
+                .push((indices[0], indices[i], indices[i + 1]));
+        }
+    }
+}
@@ -1,3 +1,7 @@
 pub mod camera;
+pub mod controller;
 pub mod drawing;
+pub mod formats;
+pub mod mesh;
+pub mod primitives;
 pub mod renderer;
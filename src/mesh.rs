@@ -0,0 +1,185 @@
+use std::{collections::HashMap, sync::Arc};
+
+use glam::{Vec2, Vec3};
+use palette::Srgba;
+
+use crate::{
+    formats::Texture,
+    renderer::{AttributeBuffer, AttributeValues, Mesh3D, Semantic, VertexAttribute},
+};
+
+mod cleanup;
+mod normals;
+mod simplify;
+mod subdivide;
+
+/// Triangles sharing a list of vertices.
+///
+/// Optional attributes are either empty or hold one value for every position.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IndexedMesh {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub tex_coords: Vec<Vec2>,
+    /// Vertices are white if there are no colors
+    pub colors: Vec<Srgba>,
+    pub triangles: Vec<(usize, usize, usize)>,
+    /// Any other attributes, such as tangents or a second set of texture coordinates
+    pub extra: Vec<(Semantic, AttributeBuffer)>,
+    /// Multiplied with the colors, sampled at `tex_coords`
+    pub texture: Option<Arc<Texture>>,
+}
+
+/// Key of a position that does not tell 0 and -0 apart
+fn position_key(p: Vec3) -> [u32; 3] {
+    (p + Vec3::ZERO).to_array().map(f32::to_bits)
+}
+
+impl IndexedMesh {
+    /// Normal of triangle `t` following its counter-clockwise winding, zero if it has no area.
+    pub fn face_normal(&self, t: (usize, usize, usize)) -> Vec3 {
+        let (a, b, c) = (
+            self.positions[t.0],
+            self.positions[t.1],
+            self.positions[t.2],
+        );
+        (b - a).cross(c - a).normalize_or_zero()
+    }
+
+    /// Angle of triangle `t` at its `corner`th vertex, zero if it has no area.
+    pub fn corner_angle(&self, t: (usize, usize, usize), corner: usize) -> f32 {
+        let corners = [t.0, t.1, t.2];
+        let a = self.positions[corners[corner]];
+        let b = self.positions[corners[(corner + 1) % 3]] - a;
+        let c = self.positions[corners[(corner + 2) % 3]] - a;
+        if b == Vec3::ZERO || c == Vec3::ZERO {
+            return 0f32;
+        }
+        b.angle_between(c)
+    }
+
+    /// Every distinct position and which of them each vertex is at, for operations that treat
+    /// vertices split for their attributes as one.
+    fn points(&self) -> (Vec<Vec3>, Vec<usize>) {
+        let mut points = vec![];
+        let mut keys = HashMap::new();
+        let point = self
+            .positions
+            .iter()
+            .map(|p| {
+                *keys.entry(position_key(*p)).or_insert_with(|| {
+                    points.push(*p);
+                    points.len() - 1
+                })
+            })
+            .collect();
+        (points, point)
+    }
+
+    /// Replaces the vertices with copies of the vertices at `sources`, with all their attributes.
+    /// Triangles are left as they are.
+    fn select(&mut self, sources: &[usize]) {
+        self.positions = pick(&self.positions, sources);
+        self.normals = pick(&self.normals, sources);
+        self.tex_coords = pick(&self.tex_coords, sources);
+        self.colors = pick(&self.colors, sources);
+        for (_, buffer) in &mut self.extra {
+            *buffer = buffer.select(sources);
+        }
+    }
+
+    /// Adds a vertex halfway along each of `edges` with the average of their attributes,
+    /// except for the position.
+    fn extend_midpoints(&mut self, edges: &[(usize, usize)]) {
+        push_midpoints(&mut self.normals, edges, |a, b| (a + b).normalize_or_zero());
+        push_midpoints(&mut self.tex_coords, edges, |a, b| (a + b) / 2f32);
+        push_midpoints(&mut self.colors, edges, mix_colors);
+        for (_, buffer) in &mut self.extra {
+            buffer.extend_midpoints(edges);
+        }
+    }
+}
+
+impl Mesh3D for IndexedMesh {
+    fn vertices(&self) -> &[Vec3] {
+        &self.positions
+    }
+
+    fn indices(&self) -> &[(usize, usize, usize)] {
+        &self.triangles
+    }
+
+    fn attributes<'a>(&'a self, attributes: &mut Vec<VertexAttribute<'a>>) {
+        let own = [
+            (Semantic::Normal, AttributeValues::Vec3(&self.normals)),
+            (
+                Semantic::TexCoord(0),
+                AttributeValues::Vec2(&self.tex_coords),
+            ),
+            (Semantic::Color, AttributeValues::Color(&self.colors)),
+        ];
+        let own = own
+            .into_iter()
+            .chain(self.extra.iter().map(|(s, b)| (s.clone(), b.values())))
+            .filter(|(_, values)| !values.is_empty())
+            .map(|(semantic, values)| VertexAttribute { semantic, values });
+        attributes.extend(own);
+    }
+
+    fn texture(&self) -> Option<&Texture> {
+        self.texture.as_deref()
+    }
+}
+
+impl AttributeBuffer {
+    /// Values of the vertices at `sources`, in that order. Empty buffers stay empty.
+    fn select(&self, sources: &[usize]) -> Self {
+        match self {
+            AttributeBuffer::Float(v) => AttributeBuffer::Float(pick(v, sources)),
+            AttributeBuffer::Vec2(v) => AttributeBuffer::Vec2(pick(v, sources)),
+            AttributeBuffer::Vec3(v) => AttributeBuffer::Vec3(pick(v, sources)),
+            AttributeBuffer::Vec4(v) => AttributeBuffer::Vec4(pick(v, sources)),
+            AttributeBuffer::Color(v) => AttributeBuffer::Color(pick(v, sources)),
+        }
+    }
+
+    /// Adds the average of the values at both ends of each of `edges`.
+    /// Empty buffers stay empty.
+    fn extend_midpoints(&mut self, edges: &[(usize, usize)]) {
+        match self {
+            AttributeBuffer::Float(v) => push_midpoints(v, edges, |a, b| (a + b) / 2f32),
+            AttributeBuffer::Vec2(v) => push_midpoints(v, edges, |a, b| (a + b) / 2f32),
+            AttributeBuffer::Vec3(v) => push_midpoints(v, edges, |a, b| (a + b) / 2f32),
+            AttributeBuffer::Vec4(v) => push_midpoints(v, edges, |a, b| (a + b) / 2f32),
+            AttributeBuffer::Color(v) => push_midpoints(v, edges, mix_colors),
+        }
+    }
+}
+
+/// Values at `sources`, in that order, or nothing if there are no values.
+fn pick<T: Copy>(values: &[T], sources: &[usize]) -> Vec<T> {
+    if values.is_empty() {
+        return vec![];
+    }
+    sources.iter().map(|i| values[*i]).collect()
+}
+
+/// Adds `mix` of the values at both ends of each of `edges`, unless there are no values.
+fn push_midpoints<T: Copy>(values: &mut Vec<T>, edges: &[(usize, usize)], mix: impl Fn(T, T) -> T) {
+    if values.is_empty() {
+        return;
+    }
+    for (a, b) in edges {
+        values.push(mix(values[*a], values[*b]));
+    }
+}
+
+/// Average of two colors, channel by channel.
+fn mix_colors(a: Srgba, b: Srgba) -> Srgba {
+    Srgba::new(
+        (a.red + b.red) / 2f32,
+        (a.green + b.green) / 2f32,
+        (a.blue + b.blue) / 2f32,
+        (a.alpha + b.alpha) / 2f32,
+    )
+}
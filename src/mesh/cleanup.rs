@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+use glam::Vec3;
+
+use crate::{
+    mesh::{position_key, IndexedMesh},
+    renderer::Mesh3D,
+};
+
+impl IndexedMesh {
+    /// Merges vertices that are at most `tolerance` apart and whose other attributes differ by
+    /// at most `tolerance` too, keeping the first of them. Attributes that should not keep
+    /// vertices apart, such as the facet normals of an STL file, have to be cleared first.
+    ///
+    /// Triangles whose corners are merged are kept, see `remove_degenerate_triangles`.
+    /// Vertices without triangles are removed.
+    pub fn weld(&mut self, tolerance: f32) {
+        // Vertices that are kept, in cells as large as the tolerance so only the cells next
+        // to a vertex have to be searched
+        let cell = |p: Vec3| -> [i64; 3] {
+            if tolerance > 0f32 {
+                (p / tolerance).floor().to_array().map(|c| c as i64)
+            } else {
+                position_key(p).map(i64::from)
+            }
+        };
+        let reach = if tolerance > 0f32 { 1 } else { 0 };
+        let mut cells: HashMap<[i64; 3], Vec<usize>> = HashMap::new();
+
+        let mut attributes = vec![];
+        self.attributes(&mut attributes);
+        let same = |i: usize, j: usize| {
+            self.positions[i].distance(self.positions[j]) <= tolerance
+                && attributes
+                    .iter()
+                    .all(|a| a.values.get(i).abs_diff_eq(a.values.get(j), tolerance))
+        };
+        let mut merged = Vec::with_capacity(self.positions.len());
+        for (i, p) in self.positions.iter().enumerate() {
+            let [x, y, z] = cell(*p);
+            let found = (-reach..=reach)
+                .flat_map(|dx| {
+                    (-reach..=reach)
+                        .flat_map(move |dy| (-reach..=reach).map(move |dz| (dx, dy, dz)))
+                })
+                .find_map(|(dx, dy, dz)| {
+                    cells
+                        .get(&[x + dx, y + dy, z + dz])?
+                        .iter()
+                        .copied()
+                        .find(|j| same(i, *j))
+                });
+            merged.push(found.unwrap_or_else(|| {
+                cells.entry([x, y, z]).or_default().push(i);
+                i
+            }));
+        }
+
+        for t in &mut self.triangles {
+            *t = (merged[t.0], merged[t.1], merged[t.2]);
+        }
+        self.remove_unused_vertices();
+    }
+
+    /// Removes the triangles without area, such as those using a vertex or position twice,
+    /// and the vertices left without triangles.
+    pub fn remove_degenerate_triangles(&mut self) {
+        let triangles = std::mem::take(&mut self.triangles);
+        self.triangles = triangles
+            .into_iter()
+            .filter(|t| self.face_normal(*t) != Vec3::ZERO)
+            .collect();
+        self.remove_unused_vertices();
+    }
+
+    /// Removes the vertices that no triangle uses, the others keep their order.
+    pub fn remove_unused_vertices(&mut self) {
+        let mut used = vec![false; self.positions.len()];
+        for t in &self.triangles {
+            for v in [t.0, t.1, t.2] {
+                used[v] = true;
+            }
+        }
+        let sources: Vec<usize> = (0..used.len()).filter(|v| used[*v]).collect();
+        if sources.len() == used.len() {
+            return;
+        }
+
+        let mut index = vec![0; used.len()];
+        for (new, old) in sources.iter().enumerate() {
+            index[*old] = new;
+        }
+        for t in &mut self.triangles {
+            *t = (index[t.0], index[t.1], index[t.2]);
+        }
+        self.select(&sources);
+    }
+}
@@ -0,0 +1,151 @@
+use std::{collections::HashMap, f32::consts::PI};
+
+use glam::{Vec3, Vec4};
+use radians::Rad32;
+
+use crate::{
+    mesh::{position_key, IndexedMesh},
+    renderer::{AttributeBuffer, Semantic},
+};
+
+impl IndexedMesh {
+    /// Normal of every triangle, see `face_normal`.
+    pub fn face_normals(&self) -> Vec<Vec3> {
+        self.triangles
+            .iter()
+            .map(|t| self.face_normal(*t))
+            .collect()
+    }
+
+    /// Replaces the normals with the average of the normals of the triangles around every vertex,
+    /// weighted by their angle at the vertex.
+    ///
+    /// Triangles are around a vertex if they have a vertex at the same position, so meshes that
+    /// share no vertices are smoothed too. A triangle only takes in the triangles whose normals
+    /// are at most `crease_angle` away from its own, so vertices on sharper edges are split
+    /// and each side keeps its own normal. Vertices without triangles are removed.
+    pub fn compute_normals(&mut self, crease_angle: Rad32) {
+        let faces = self.face_normals();
+        let cos_crease = crease_angle.val().cos();
+        let mut around: HashMap<[u32; 3], Vec<(usize, f32)>> = HashMap::new();
+        for (i, t) in self.triangles.iter().enumerate() {
+            for (corner, v) in [t.0, t.1, t.2].into_iter().enumerate() {
+                let weight = self.corner_angle(*t, corner);
+                around
+                    .entry(position_key(self.positions[v]))
+                    .or_default()
+                    .push((i, weight));
+            }
+        }
+
+        // Corners that end up with the same normal share their vertex again
+        let mut sources = vec![];
+        let mut normals = vec![];
+        let mut vertices = HashMap::new();
+        let mut triangles = Vec::with_capacity(self.triangles.len());
+        for (i, t) in self.triangles.iter().enumerate() {
+            let mut corner = |v: usize| {
+                let normal = around[&position_key(self.positions[v])]
+                    .iter()
+                    .filter(|(j, _)| faces[*j].dot(faces[i]) >= cos_crease)
+                    .map(|(j, weight)| faces[*j] * *weight)
+                    .sum::<Vec3>()
+                    .normalize_or_zero();
+                *vertices
+                    .entry((v, normal.to_array().map(f32::to_bits)))
+                    .or_insert_with(|| {
+                        sources.push(v);
+                        normals.push(normal);
+                        sources.len() - 1
+                    })
+            };
+            triangles.push((corner(t.0), corner(t.1), corner(t.2)));
+        }
+
+        self.select(&sources);
+        self.normals = normals;
+        self.triangles = triangles;
+    }
+
+    /// Computes tangents from the texture coordinates like MikkTSpace and keeps them in `extra`
+    /// as `Semantic::Tangent`. The tangent of a vertex points towards increasing u
+    /// perpendicular to the normal, w is 1 if increasing v is along the cross product of the
+    /// normal and the tangent and -1 if the texture is mirrored.
+    ///
+    /// Triangles are weighted by their angle at the vertex. Vertices shared by mirrored and
+    /// not mirrored triangles are split and vertices without triangles are removed.
+    /// Smooth normals are computed first if there are none, nothing happens without
+    /// texture coordinates.
+    pub fn compute_tangents(&mut self) {
+        if self.tex_coords.is_empty() {
+            return;
+        }
+        if self.normals.is_empty() {
+            self.compute_normals(Rad32::new(PI));
+        }
+
+        // Directions of increasing u and v on every triangle
+        let frames: Vec<(Vec3, Vec3)> = self
+            .triangles
+            .iter()
+            .map(|t| {
+                let (p, uv) = (&self.positions, &self.tex_coords);
+                let (e1, e2) = (p[t.1] - p[t.0], p[t.2] - p[t.0]);
+                let (d1, d2) = (uv[t.1] - uv[t.0], uv[t.2] - uv[t.0]);
+                let det = d1.x * d2.y - d2.x * d1.y;
+                if det == 0f32 {
+                    return (Vec3::ZERO, Vec3::ZERO);
+                }
+                let tangent = (e1 * d2.y - e2 * d1.y) / det;
+                let bitangent = (e2 * d1.x - e1 * d2.x) / det;
+                (tangent, bitangent)
+            })
+            .collect();
+
+        let mut sources = vec![];
+        let mut sums: Vec<(Vec3, Vec3)> = vec![];
+        let mut vertices = HashMap::new();
+        let mut triangles = Vec::with_capacity(self.triangles.len());
+        for (i, t) in self.triangles.iter().enumerate() {
+            let (tangent, bitangent) = frames[i];
+            let mirrored = self.face_normal(*t).cross(tangent).dot(bitangent) < 0f32;
+            let mut corner = |corner: usize, v: usize| {
+                let index = *vertices.entry((v, mirrored)).or_insert_with(|| {
+                    sources.push(v);
+                    sums.push((Vec3::ZERO, Vec3::ZERO));
+                    sources.len() - 1
+                });
+                let weight = self.corner_angle(*t, corner);
+                sums[index].0 += tangent * weight;
+                sums[index].1 += bitangent * weight;
+                index
+            };
+            triangles.push((corner(0, t.0), corner(1, t.1), corner(2, t.2)));
+        }
+
+        let tangents = sources
+            .iter()
+            .zip(&sums)
+            .map(|(v, (tangent, bitangent))| {
+                // Gram-Schmidt, any direction in the surface will do if u does not change
+                let normal = self.normals[*v];
+                let tangent = (*tangent - normal * normal.dot(*tangent))
+                    .try_normalize()
+                    .unwrap_or_else(|| normal.any_orthonormal_vector());
+                let w = if normal.cross(tangent).dot(*bitangent) < 0f32 {
+                    -1f32
+                } else {
+                    1f32
+                };
+                Vec4::from((tangent, w))
+            })
+            .collect();
+
+        self.select(&sources);
+        self.triangles = triangles;
+        self.extra
+            .retain(|(semantic, _)| *semantic != Semantic::Tangent);
+        self.extra
+            .push((Semantic::Tangent, AttributeBuffer::Vec4(tangents)));
+    }
+}
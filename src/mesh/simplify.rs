@@ -0,0 +1,241 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    ops::Add,
+};
+
+use glam::DVec3;
+
+use crate::mesh::IndexedMesh;
+
+/// How much more moving a border vertex away from the border costs than moving a vertex away
+/// from a triangle of the same size
+const BORDER_WEIGHT: f64 = 100f64;
+
+/// Weighted sum of the squared distances to some planes, as the upper half of a symmetric
+/// 4×4 matrix.
+#[derive(Debug, Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    fn plane(normal: DVec3, point: DVec3, weight: f64) -> Self {
+        let [a, b, c] = normal.to_array();
+        let d = -normal.dot(point);
+        Self(
+            [
+                a * a,
+                a * b,
+                a * c,
+                a * d,
+                b * b,
+                b * c,
+                b * d,
+                c * c,
+                c * d,
+                d * d,
+            ]
+            .map(|q| q * weight),
+        )
+    }
+
+    fn error(&self, p: DVec3) -> f64 {
+        let [aa, ab, ac, ad, bb, bc, bd, cc, cd, dd] = self.0;
+        let [x, y, z] = p.to_array();
+        aa * x * x
+            + bb * y * y
+            + cc * z * z
+            + 2f64 * (ab * x * y + ac * x * z + bc * y * z + ad * x + bd * y + cd * z)
+            + dd
+    }
+}
+
+impl Add for Quadric {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        for (q, o) in self.0.iter_mut().zip(other.0) {
+            *q += o;
+        }
+        self
+    }
+}
+
+fn normal(points: &[DVec3], [a, b, c]: [usize; 3]) -> DVec3 {
+    (points[b] - points[a])
+        .cross(points[c] - points[a])
+        .normalize_or_zero()
+}
+
+/// Entry of the queue of edges to collapse, cheapest first: the cost, the position that goes,
+/// the one it goes to and the versions of both when the cost was found
+type Collapse = Reverse<(u64, usize, usize, u32, u32)>;
+
+fn collapse(
+    points: &[DVec3],
+    quadrics: &[Quadric],
+    versions: &[u32],
+    a: usize,
+    b: usize,
+) -> Collapse {
+    let quadric = quadrics[a] + quadrics[b];
+    let (to_a, to_b) = (quadric.error(points[a]), quadric.error(points[b]));
+    let (cost, from, to) = if to_b <= to_a {
+        (to_b, a, b)
+    } else {
+        (to_a, b, a)
+    };
+    // Bits of floats that are not negative sort like the floats
+    Reverse((
+        cost.max(0f64).to_bits(),
+        from,
+        to,
+        versions[from],
+        versions[to],
+    ))
+}
+
+impl IndexedMesh {
+    /// Collapses the edges that change the shape least until at most `target_triangles` are
+    /// left, measuring the change with quadric error metrics. Stops early if every edge left
+    /// would flip a triangle over or tear a seam.
+    ///
+    /// Every collapse moves the vertices at one position onto their neighbours at another, so
+    /// the vertices left keep their positions and attributes and seams where vertices are split
+    /// for their attributes stay closed. Borders stay in place as long as possible. Triangles
+    /// with two corners at the same position and vertices without triangles are removed.
+    pub fn simplify(&mut self, target_triangles: usize) {
+        // Edges are collapsed between positions, the vertices at them follow
+        let (points, point) = self.points();
+        let points: Vec<_> = points.iter().map(|p| p.as_dvec3()).collect();
+        let corners = |t: [usize; 3]| t.map(|v| point[v]);
+        let mut triangles: Vec<_> = self
+            .triangles
+            .iter()
+            .map(|t| [t.0, t.1, t.2])
+            .filter(|t| {
+                let [a, b, c] = corners(*t);
+                a != b && b != c && c != a
+            })
+            .map(Some)
+            .collect();
+        let mut around = vec![vec![]; points.len()];
+        let mut quadrics = vec![Quadric::default(); points.len()];
+        let mut edges: HashMap<(usize, usize), u32> = HashMap::new();
+        for (i, t) in triangles.iter().flatten().enumerate() {
+            // Planes of the triangles weighted by their area
+            let [a, b, c] = corners(*t);
+            let cross = (points[b] - points[a]).cross(points[c] - points[a]);
+            let plane = Quadric::plane(cross.normalize_or_zero(), points[a], cross.length() / 2f64);
+            for p in [a, b, c] {
+                around[p].push(i);
+                quadrics[p] = quadrics[p] + plane;
+            }
+            for (u, v) in [(a, b), (b, c), (c, a)] {
+                *edges.entry((u.min(v), u.max(v))).or_default() += 1;
+            }
+        }
+        // Planes through the border edges, perpendicular to their triangle
+        for t in triangles.iter().flatten() {
+            let [a, b, c] = corners(*t);
+            let normal = normal(&points, [a, b, c]);
+            for (u, v) in [(a, b), (b, c), (c, a)] {
+                if edges[&(u.min(v), u.max(v))] == 1 {
+                    let edge = points[v] - points[u];
+                    let weight = BORDER_WEIGHT * edge.length_squared();
+                    let plane =
+                        Quadric::plane(edge.cross(normal).normalize_or_zero(), points[u], weight);
+                    quadrics[u] = quadrics[u] + plane;
+                    quadrics[v] = quadrics[v] + plane;
+                }
+            }
+        }
+
+        // Collapses that are out of date once a position moved are skipped
+        let mut versions = vec![0u32; points.len()];
+        let mut queue: BinaryHeap<_> = edges
+            .keys()
+            .map(|(u, v)| collapse(&points, &quadrics, &versions, *u, *v))
+            .collect();
+        let mut left = triangles.len();
+        while left > target_triangles {
+            let Some(Reverse((_, from, to, from_version, to_version))) = queue.pop() else {
+                break;
+            };
+            if versions[from] != from_version || versions[to] != to_version {
+                continue;
+            }
+
+            // Every vertex that goes needs a single vertex to go to across an edge,
+            // which has the same attributes on its side of a seam
+            let mut targets: Vec<(usize, usize)> = vec![];
+            let mut torn = false;
+            for t in around[from].iter().filter_map(|t| triangles[*t]) {
+                let going = t.into_iter().find(|v| point[*v] == from).unwrap();
+                let Some(staying) = t.into_iter().find(|v| point[*v] == to) else {
+                    continue;
+                };
+                match targets.iter().find(|(v, _)| *v == going) {
+                    Some((_, target)) => torn |= *target != staying,
+                    None => targets.push((going, staying)),
+                }
+            }
+            let target = |v: usize| {
+                targets
+                    .iter()
+                    .find(|(going, _)| *going == v)
+                    .map(|(_, t)| *t)
+            };
+            torn |= around[from]
+                .iter()
+                .filter_map(|t| triangles[*t])
+                .any(|t| t.iter().all(|v| target(*v).is_none()));
+            let flips = around[from]
+                .iter()
+                .filter_map(|t| triangles[*t])
+                .filter(|t| !corners(*t).contains(&to))
+                .any(|t| {
+                    let before = normal(&points, corners(t));
+                    let after = normal(&points, corners(t.map(|v| target(v).unwrap_or(v))));
+                    before != DVec3::ZERO && before.dot(after) <= 0f64
+                });
+            if torn || flips {
+                continue;
+            }
+
+            for t in std::mem::take(&mut around[from]) {
+                let Some(vertices) = triangles[t] else {
+                    continue;
+                };
+                if corners(vertices).contains(&to) {
+                    triangles[t] = None;
+                    left -= 1;
+                } else {
+                    triangles[t] = Some(vertices.map(|v| target(v).unwrap_or(v)));
+                    around[to].push(t);
+                }
+            }
+            quadrics[to] = quadrics[to] + quadrics[from];
+            versions[from] += 1;
+            versions[to] += 1;
+
+            around[to].retain(|t| triangles[*t].is_some());
+            let mut neighbours: Vec<usize> = around[to]
+                .iter()
+                .flat_map(|t| corners(triangles[*t].unwrap()))
+                .filter(|p| *p != to)
+                .collect();
+            neighbours.sort_unstable();
+            neighbours.dedup();
+            for p in neighbours {
+                queue.push(collapse(&points, &quadrics, &versions, to, p));
+            }
+        }
+
+        self.triangles = triangles
+            .into_iter()
+            .flatten()
+            .map(|[a, b, c]| (a, b, c))
+            .collect();
+        self.remove_unused_vertices();
+    }
+}
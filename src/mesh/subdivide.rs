@@ -0,0 +1,106 @@
+use std::{collections::HashMap, f32::consts::PI};
+
+use glam::Vec3;
+use radians::Rad32;
+
+use crate::{mesh::IndexedMesh, renderer::Semantic};
+
+impl IndexedMesh {
+    /// Splits every triangle in four with Loop subdivision, so the surface gets smoother with
+    /// every step.
+    ///
+    /// Vertices at the same position move together, so seams where vertices are split for
+    /// their attributes stay closed. Borders are only smoothed along themselves, except where
+    /// more than two of them meet. The other attributes are interpolated linearly, smooth
+    /// normals are computed again if there are any and so are tangents, which are dropped
+    /// without texture coordinates.
+    ///
+    /// Creases are rounded off like the rest of the surface, so flat shaded meshes come out
+    /// smooth shaded.
+    pub fn subdivide(&mut self) {
+        // The surface is smoothed as if vertices at the same position were one point
+        let (points, point) = self.points();
+
+        // Points opposite every edge, which is on a border unless there are two
+        let mut opposite: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for t in &self.triangles {
+            let [a, b, c] = [t.0, t.1, t.2].map(|v| point[v]);
+            for (u, v, w) in [(a, b, c), (b, c, a), (c, a, b)] {
+                if u != v {
+                    opposite.entry((u.min(v), u.max(v))).or_default().push(w);
+                }
+            }
+        }
+        let mut neighbours = vec![vec![]; points.len()];
+        let mut border = vec![vec![]; points.len()];
+        for (&(u, v), w) in &opposite {
+            neighbours[u].push(v);
+            neighbours[v].push(u);
+            if w.len() != 2 {
+                border[u].push(v);
+                border[v].push(u);
+            }
+        }
+
+        // Sorted so the sums do not depend on the order of the map
+        let sum = |points: &[Vec3], indices: &mut Vec<usize>| {
+            indices.sort_unstable();
+            indices.iter().map(|i| points[*i]).sum::<Vec3>()
+        };
+        let even: Vec<Vec3> = (0..points.len())
+            .map(|i| match border[i].len() {
+                0 if !neighbours[i].is_empty() => {
+                    let n = neighbours[i].len() as f32;
+                    let beta =
+                        (5f32 / 8f32 - (3f32 / 8f32 + (2f32 * PI / n).cos() / 4f32).powi(2)) / n;
+                    points[i] * (1f32 - n * beta) + sum(&points, &mut neighbours[i]) * beta
+                }
+                2 => points[i] * 0.75f32 + sum(&points, &mut border[i]) * 0.125f32,
+                _ => points[i],
+            })
+            .collect();
+        let odd = |u: usize, v: usize| {
+            let (u, v) = (point[u], point[v]);
+            match opposite.get(&(u.min(v), u.max(v))).map(Vec::as_slice) {
+                Some([c, d]) => {
+                    (points[u] + points[v]) * 0.375f32 + (points[*c] + points[*d]) * 0.125f32
+                }
+                _ => (points[u] + points[v]) / 2f32,
+            }
+        };
+
+        let vertices = self.positions.len();
+        let mut midpoints = HashMap::new();
+        let mut edges = vec![];
+        let mut triangles = Vec::with_capacity(self.triangles.len() * 4);
+        for t in &self.triangles {
+            let mut midpoint = |u: usize, v: usize| {
+                *midpoints.entry((u.min(v), u.max(v))).or_insert_with(|| {
+                    edges.push((u, v));
+                    vertices + edges.len() - 1
+                })
+            };
+            let (ab, bc, ca) = (midpoint(t.0, t.1), midpoint(t.1, t.2), midpoint(t.2, t.0));
+            triangles.extend([(t.0, ab, ca), (ab, t.1, bc), (ca, bc, t.2), (ab, bc, ca)]);
+        }
+
+        let positions = (0..vertices)
+            .map(|v| even[point[v]])
+            .chain(edges.iter().map(|(u, v)| odd(*u, *v)))
+            .collect();
+        // Averaged tangents are neither unit length nor do they keep their handedness
+        let tangents = self.extra.len();
+        self.extra
+            .retain(|(semantic, _)| *semantic != Semantic::Tangent);
+        let tangents = tangents != self.extra.len();
+        self.extend_midpoints(&edges);
+        self.positions = positions;
+        self.triangles = triangles;
+        if !self.normals.is_empty() {
+            self.compute_normals(Rad32::new(PI));
+        }
+        if tangents {
+            self.compute_tangents();
+        }
+    }
+}
@@ -0,0 +1,539 @@
+//! Procedurally generated meshes.
+//!
+//! Every primitive is centered on the origin with y up, fits in a unit cube with the
+//! default settings and has counter-clockwise triangles seen from the outside.
+//! Texture coordinates have v pointing up.
+
+use std::{collections::HashMap, f32::consts::PI};
+
+use glam::{Vec2, Vec3};
+use palette::Srgba;
+
+use crate::mesh::IndexedMesh;
+
+const WHITE: Srgba = Srgba::new(1f32, 1f32, 1f32, 1f32);
+
+/// Flat rectangle in the xz plane facing up.
+#[derive(Debug, Clone, Copy)]
+pub struct Plane {
+    /// Extent along x and z
+    pub size: Vec2,
+    /// Number of quads along x and z
+    pub subdivisions: (u32, u32),
+    pub color: Srgba,
+}
+
+impl Default for Plane {
+    fn default() -> Self {
+        Self {
+            size: Vec2::ONE,
+            subdivisions: (1, 1),
+            color: WHITE,
+        }
+    }
+}
+
+impl Plane {
+    pub fn mesh(&self) -> IndexedMesh {
+        let (columns, rows) = (self.subdivisions.0.max(1), self.subdivisions.1.max(1));
+        let mesh = grid(columns, rows, |u, v| {
+            let position = Vec3::new((u - 0.5f32) * self.size.x, 0f32, (0.5f32 - v) * self.size.y);
+            (position, Vec3::Y, Vec2::new(u, v))
+        });
+        paint(mesh, self.color)
+    }
+}
+
+/// Box with flat shaded faces, each face has the whole texture.
+#[derive(Debug, Clone, Copy)]
+pub struct Cube {
+    pub size: Vec3,
+    /// Number of quads along each edge
+    pub subdivisions: u32,
+    pub color: Srgba,
+}
+
+impl Default for Cube {
+    fn default() -> Self {
+        Self {
+            size: Vec3::ONE,
+            subdivisions: 1,
+            color: WHITE,
+        }
+    }
+}
+
+impl Cube {
+    pub fn mesh(&self) -> IndexedMesh {
+        let n = self.subdivisions.max(1);
+        // Normal, right and up of every face as seen from the outside
+        let faces = [
+            (Vec3::X, Vec3::NEG_Z, Vec3::Y),
+            (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+            (Vec3::Y, Vec3::X, Vec3::NEG_Z),
+            (Vec3::NEG_Y, Vec3::X, Vec3::Z),
+            (Vec3::Z, Vec3::X, Vec3::Y),
+            (Vec3::NEG_Z, Vec3::NEG_X, Vec3::Y),
+        ];
+        let mut mesh = IndexedMesh::default();
+        for (normal, right, up) in faces {
+            let face = grid(n, n, |u, v| {
+                let position = normal * 0.5f32 + right * (u - 0.5f32) + up * (v - 0.5f32);
+                (position * self.size, normal, Vec2::new(u, v))
+            });
+            append(&mut mesh, face);
+        }
+        paint(mesh, self.color)
+    }
+}
+
+/// Sphere made of rings of quads between the poles.
+#[derive(Debug, Clone, Copy)]
+pub struct UvSphere {
+    pub radius: f32,
+    /// Number of quads around the y axis
+    pub segments: u32,
+    /// Number of quads from pole to pole
+    pub rings: u32,
+    pub color: Srgba,
+}
+
+impl Default for UvSphere {
+    fn default() -> Self {
+        Self {
+            radius: 0.5f32,
+            segments: 32,
+            rings: 16,
+            color: WHITE,
+        }
+    }
+}
+
+impl UvSphere {
+    pub fn mesh(&self) -> IndexedMesh {
+        let rings = self.rings.max(2);
+        let profile = (0..=rings)
+            .map(|j| {
+                let v = j as f32 / rings as f32;
+                let normal = pole_safe(PI * v);
+                (normal * self.radius, normal, v)
+            })
+            .collect::<Vec<_>>();
+        paint(revolve(self.segments.max(3), &profile), self.color)
+    }
+}
+
+/// Sphere made of evenly sized triangles by subdividing an icosahedron.
+#[derive(Debug, Clone, Copy)]
+pub struct Icosphere {
+    pub radius: f32,
+    /// Every subdivision splits each triangle in 4
+    pub subdivisions: u32,
+    pub color: Srgba,
+}
+
+impl Default for Icosphere {
+    fn default() -> Self {
+        Self {
+            radius: 0.5f32,
+            subdivisions: 2,
+            color: WHITE,
+        }
+    }
+}
+
+impl Icosphere {
+    pub fn mesh(&self) -> IndexedMesh {
+        let t = (1f32 + 5f32.sqrt()) / 2f32;
+        let mut directions: Vec<Vec3> = [
+            (-1f32, t, 0f32),
+            (1f32, t, 0f32),
+            (-1f32, -t, 0f32),
+            (1f32, -t, 0f32),
+            (0f32, -1f32, t),
+            (0f32, 1f32, t),
+            (0f32, -1f32, -t),
+            (0f32, 1f32, -t),
+            (t, 0f32, -1f32),
+            (t, 0f32, 1f32),
+            (-t, 0f32, -1f32),
+            (-t, 0f32, 1f32),
+        ]
+        .into_iter()
+        .map(|(x, y, z)| Vec3::new(x, y, z).normalize())
+        .collect();
+        let mut triangles = vec![
+            (0, 11, 5),
+            (0, 5, 1),
+            (0, 1, 7),
+            (0, 7, 10),
+            (0, 10, 11),
+            (1, 5, 9),
+            (5, 11, 4),
+            (11, 10, 2),
+            (10, 7, 6),
+            (7, 1, 8),
+            (3, 9, 4),
+            (3, 4, 2),
+            (3, 2, 6),
+            (3, 6, 8),
+            (3, 8, 9),
+            (4, 9, 5),
+            (2, 4, 11),
+            (6, 2, 10),
+            (8, 6, 7),
+            (9, 8, 1),
+        ];
+
+        for _ in 0..self.subdivisions {
+            let mut midpoints = HashMap::new();
+            let mut midpoint = |a: usize, b: usize| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    directions.push((directions[a] + directions[b]).normalize());
+                    directions.len() - 1
+                })
+            };
+            triangles = triangles
+                .into_iter()
+                .flat_map(|(a, b, c)| {
+                    let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                    [(a, ab, ca), (b, bc, ab), (c, ca, bc), (ab, bc, ca)]
+                })
+                .collect();
+        }
+
+        // Spherical texture coordinates need their own vertices where triangles cross
+        // the seam at the back and at the poles, where u depends on the triangle
+        let mut mesh = IndexedMesh::default();
+        let mut vertices = HashMap::new();
+        for (a, b, c) in triangles {
+            let corners = [a, b, c];
+            let mut uvs = corners.map(|i| {
+                let d = directions[i];
+                Vec2::new(
+                    0.5f32 + d.x.atan2(d.z) / (2f32 * PI),
+                    0.5f32 + d.y.clamp(-1f32, 1f32).asin() / PI,
+                )
+            });
+            let pole = corners.map(|i| directions[i].x == 0f32 && directions[i].z == 0f32);
+            let (min, max) = (0..3)
+                .filter(|k| !pole[*k])
+                .fold((f32::MAX, f32::MIN), |(min, max), k| {
+                    (min.min(uvs[k].x), max.max(uvs[k].x))
+                });
+            if max - min > 0.5f32 {
+                for uv in &mut uvs {
+                    if uv.x < 0.5f32 {
+                        uv.x += 1f32;
+                    }
+                }
+            }
+            for k in (0..3).filter(|k| pole[*k]) {
+                uvs[k].x = (uvs[(k + 1) % 3].x + uvs[(k + 2) % 3].x) / 2f32;
+            }
+
+            let mut vertex = |k: usize| {
+                let key = (corners[k], uvs[k].x.to_bits(), uvs[k].y.to_bits());
+                *vertices.entry(key).or_insert_with(|| {
+                    let d = directions[corners[k]];
+                    mesh.positions.push(d * self.radius);
+                    mesh.normals.push(d);
+                    mesh.tex_coords.push(uvs[k]);
+                    mesh.positions.len() - 1
+                })
+            };
+            let triangle = (vertex(0), vertex(1), vertex(2));
+            mesh.triangles.push(triangle);
+        }
+        paint(mesh, self.color)
+    }
+}
+
+/// Closed cylinder along the y axis.
+#[derive(Debug, Clone, Copy)]
+pub struct Cylinder {
+    pub radius: f32,
+    pub height: f32,
+    /// Number of quads around the y axis
+    pub segments: u32,
+    /// Number of quads from bottom to top
+    pub rings: u32,
+    pub color: Srgba,
+}
+
+impl Default for Cylinder {
+    fn default() -> Self {
+        Self {
+            radius: 0.5f32,
+            height: 1f32,
+            segments: 32,
+            rings: 1,
+            color: WHITE,
+        }
+    }
+}
+
+impl Cylinder {
+    pub fn mesh(&self) -> IndexedMesh {
+        let (segments, rings) = (self.segments.max(3), self.rings.max(1));
+        let bottom = -self.height / 2f32;
+        let side = (0..=rings)
+            .map(|j| {
+                let v = j as f32 / rings as f32;
+                (Vec2::new(self.radius, bottom + self.height * v), Vec2::X, v)
+            })
+            .collect::<Vec<_>>();
+
+        let mut mesh = revolve(segments, &side);
+        append(&mut mesh, cap(self.radius, bottom, segments, false));
+        append(&mut mesh, cap(self.radius, -bottom, segments, true));
+        paint(mesh, self.color)
+    }
+}
+
+/// Cone along the y axis with its apex at the top.
+#[derive(Debug, Clone, Copy)]
+pub struct Cone {
+    /// Radius of the base
+    pub radius: f32,
+    pub height: f32,
+    /// Number of quads around the y axis
+    pub segments: u32,
+    /// Number of rings from the base to the apex
+    pub rings: u32,
+    pub color: Srgba,
+}
+
+impl Default for Cone {
+    fn default() -> Self {
+        Self {
+            radius: 0.5f32,
+            height: 1f32,
+            segments: 32,
+            rings: 1,
+            color: WHITE,
+        }
+    }
+}
+
+impl Cone {
+    pub fn mesh(&self) -> IndexedMesh {
+        let (segments, rings) = (self.segments.max(3), self.rings.max(1));
+        let bottom = -self.height / 2f32;
+        let normal = Vec2::new(self.height, self.radius).normalize_or_zero();
+        let side = (0..=rings)
+            .map(|j| {
+                let v = j as f32 / rings as f32;
+                let point = Vec2::new(self.radius * (1f32 - v), bottom + self.height * v);
+                (point, normal, v)
+            })
+            .collect::<Vec<_>>();
+
+        let mut mesh = revolve(segments, &side);
+        append(&mut mesh, cap(self.radius, bottom, segments, false));
+        paint(mesh, self.color)
+    }
+}
+
+/// Ring around the y axis.
+#[derive(Debug, Clone, Copy)]
+pub struct Torus {
+    /// Distance from the center to the middle of the tube
+    pub major_radius: f32,
+    /// Radius of the tube
+    pub minor_radius: f32,
+    /// Number of quads around the y axis
+    pub segments: u32,
+    /// Number of quads around the tube
+    pub sides: u32,
+    pub color: Srgba,
+}
+
+impl Default for Torus {
+    fn default() -> Self {
+        Self {
+            major_radius: 0.35f32,
+            minor_radius: 0.15f32,
+            segments: 32,
+            sides: 16,
+            color: WHITE,
+        }
+    }
+}
+
+impl Torus {
+    pub fn mesh(&self) -> IndexedMesh {
+        let sides = self.sides.max(3);
+        let tube = (0..=sides)
+            .map(|j| {
+                // Starts on the outside and goes over the top
+                let v = j as f32 / sides as f32;
+                let (sin, cos) = (2f32 * PI * v.fract()).sin_cos();
+                let normal = Vec2::new(cos, sin);
+                let point = Vec2::new(self.major_radius, 0f32) + normal * self.minor_radius;
+                (point, normal, v)
+            })
+            .collect::<Vec<_>>();
+        paint(revolve(self.segments.max(3), &tube), self.color)
+    }
+}
+
+/// Cylinder along the y axis closed by two half spheres.
+#[derive(Debug, Clone, Copy)]
+pub struct Capsule {
+    pub radius: f32,
+    /// Height including the half spheres
+    pub height: f32,
+    /// Number of quads around the y axis
+    pub segments: u32,
+    /// Number of quads from the pole to the equator of each half sphere
+    pub rings: u32,
+    pub color: Srgba,
+}
+
+impl Default for Capsule {
+    fn default() -> Self {
+        Self {
+            radius: 0.25f32,
+            height: 1f32,
+            segments: 32,
+            rings: 8,
+            color: WHITE,
+        }
+    }
+}
+
+impl Capsule {
+    pub fn mesh(&self) -> IndexedMesh {
+        let rings = self.rings.max(1);
+        let offset = (self.height / 2f32 - self.radius).max(0f32);
+        // v follows the length of the outline
+        let length = 2f32 * offset + PI * self.radius;
+        let profile = (0..=2 * rings + 1)
+            .map(|j| {
+                // The upper half sphere starts where the lower one ends
+                let (angle, y, arc) = if j <= rings {
+                    (j as f32 / rings as f32 * PI / 2f32, -offset, 0f32)
+                } else {
+                    let j = j - rings - 1;
+                    (
+                        PI / 2f32 + j as f32 / rings as f32 * PI / 2f32,
+                        offset,
+                        2f32 * offset,
+                    )
+                };
+                let normal = pole_safe(angle);
+                let point = normal * self.radius + Vec2::new(0f32, y);
+                let v = if length > 0f32 {
+                    (arc + angle * self.radius) / length
+                } else {
+                    0f32
+                };
+                (point, normal, v)
+            })
+            .collect::<Vec<_>>();
+        paint(revolve(self.segments.max(3), &profile), self.color)
+    }
+}
+
+/// Point of the unit half circle from the bottom (0) over the right to the top (π),
+/// exactly on the axis at both ends.
+fn pole_safe(angle: f32) -> Vec2 {
+    let (sin, cos) = angle.sin_cos();
+    let radius = if angle <= 0f32 || angle >= PI {
+        0f32
+    } else {
+        sin
+    };
+    Vec2::new(radius, -cos)
+}
+
+/// Builds `columns` × `rows` quads from `vertex(u, v)`, which gives the position, normal
+/// and texture coordinates at `u` and `v` in [0; 1]. Triangles are counter-clockwise around
+/// the cross product of the directions of increasing u and v, triangles with two corners
+/// at the same position are left out.
+fn grid(columns: u32, rows: u32, vertex: impl Fn(f32, f32) -> (Vec3, Vec3, Vec2)) -> IndexedMesh {
+    let mut mesh = IndexedMesh::default();
+    for j in 0..=rows {
+        for i in 0..=columns {
+            let (position, normal, uv) = vertex(i as f32 / columns as f32, j as f32 / rows as f32);
+            mesh.positions.push(position);
+            mesh.normals.push(normal);
+            mesh.tex_coords.push(uv);
+        }
+    }
+
+    let index = |i: u32, j: u32| (j * (columns + 1) + i) as usize;
+    for j in 0..rows {
+        for i in 0..columns {
+            let (a, b, c, d) = (
+                index(i, j),
+                index(i + 1, j),
+                index(i + 1, j + 1),
+                index(i, j + 1),
+            );
+            for t in [(a, b, c), (a, c, d)] {
+                let p = [
+                    mesh.positions[t.0],
+                    mesh.positions[t.1],
+                    mesh.positions[t.2],
+                ];
+                if p[0] != p[1] && p[1] != p[2] && p[2] != p[0] {
+                    mesh.triangles.push(t);
+                }
+            }
+        }
+    }
+    mesh
+}
+
+/// Surface of revolution around the y axis. `profile` lists the points of the outline as
+/// (distance from the axis, height), their normals in the same plane and v, from the bottom
+/// to the top of the outside.
+fn revolve(segments: u32, profile: &[(Vec2, Vec2, f32)]) -> IndexedMesh {
+    let rows = profile.len() as u32 - 1;
+    grid(segments, rows, |u, v| {
+        let (point, normal, v) = profile[(v * rows as f32).round() as usize];
+        // The last column is at angle 0 again so the seam closes exactly
+        let (sin, cos) = (2f32 * PI * u.fract()).sin_cos();
+        // Starts at the back so the seam is out of sight from the front
+        let around = |p: Vec2| Vec3::new(-p.x * sin, p.y, -p.x * cos);
+        (around(point), around(normal), Vec2::new(u, v))
+    })
+}
+
+/// Disc closing a surface of revolution at `height`, with the texture projected from above
+/// or below.
+fn cap(radius: f32, height: f32, segments: u32, top: bool) -> IndexedMesh {
+    let (rim, center) = (Vec2::new(radius, height), Vec2::new(0f32, height));
+    let normal = if top { Vec2::Y } else { Vec2::NEG_Y };
+    let profile = if top {
+        [(rim, normal, 0f32), (center, normal, 1f32)]
+    } else {
+        [(center, normal, 0f32), (rim, normal, 1f32)]
+    };
+    let mut mesh = revolve(segments, &profile);
+    let flip = if top { -1f32 } else { 1f32 };
+    for (uv, p) in mesh.tex_coords.iter_mut().zip(&mesh.positions) {
+        *uv = Vec2::new(p.x, flip * p.z) / (2f32 * radius).max(f32::MIN_POSITIVE) + 0.5f32;
+    }
+    mesh
+}
+
+fn append(mesh: &mut IndexedMesh, other: IndexedMesh) {
+    let offset = mesh.positions.len();
+    mesh.positions.extend(other.positions);
+    mesh.normals.extend(other.normals);
+    mesh.tex_coords.extend(other.tex_coords);
+    mesh.triangles.extend(
+        other
+            .triangles
+            .into_iter()
+            .map(|(a, b, c)| (a + offset, b + offset, c + offset)),
+    );
+}
+
+fn paint(mut mesh: IndexedMesh, color: Srgba) -> IndexedMesh {
+    mesh.colors = vec![color; mesh.positions.len()];
+    mesh
+}
@@ -1,9 +1,26 @@
+mod background;
+mod compositor;
+mod depth;
+mod depth_of_field;
 mod drawifier;
+mod raster;
 mod renderer_3d;
+mod scene;
+mod transparency;
+mod vertex;
+
+use std::ops::Range;
 
 use crate::camera::Camera;
+pub use background::*;
+pub use compositor::*;
+pub use depth::*;
+pub use depth_of_field::*;
 pub use drawifier::Drawifier;
 pub use renderer_3d::*;
+pub use scene::*;
+pub use transparency::Transparency;
+pub use vertex::*;
 
 pub struct World<R: Renderer> {
     pub camera: Camera,
@@ -23,3 +40,14 @@ pub trait Renderer {
     fn render(&self, camera: &Camera, objects: &[Self::Renderable], frame: &mut [&mut [u8]]);
     fn set_output_dimensions(&mut self, width: u32, height: u32);
 }
+
+/// Indices of pixels `xs` of row `y` of a frame that is `width` pixels wide.
+///
+/// The frame may be smaller than the output dimensions for a moment after a resize,
+/// so the range is clamped to the pixels it has.
+pub(crate) fn frame_row<T>(frame: &[T], width: u32, y: i32, xs: Range<i32>) -> Range<usize> {
+    let row = width as usize * y as usize;
+    let start = (row + xs.start as usize).min(frame.len());
+    let end = (row + xs.end as usize).min(frame.len());
+    start..end
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use super::frame_row;
+use crate::{drawing::Rect, formats::Texture};
+use palette::{Mix, Srgba};
+
+/// What a renderer puts into the frame before drawing anything.
+#[derive(Debug, Clone)]
+pub enum Background {
+    Solid(Srgba),
+    /// Fully transparent black
+    Transparent,
+    /// Blends from `top` at the first row to `bottom` at the last one
+    VerticalGradient {
+        top: Srgba,
+        bottom: Srgba,
+    },
+    /// Blends from `left` at the first column to `right` at the last one
+    HorizontalGradient {
+        left: Srgba,
+        right: Srgba,
+    },
+    /// Image stretched over the whole output
+    Image(Arc<Texture>),
+    /// Leaves the frame as is, so renderers can draw over each other
+    Keep,
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Self::Solid(Srgba::new(0f32, 0f32, 0f32, 1f32))
+    }
+}
+
+/// Pixel of `image` at `(x, y)` when it is stretched over `output_dimensions`.
+fn stretch(image: &Texture, x: u32, y: u32, output_dimensions: (u32, u32)) -> [u8; 4] {
+    // Nearest neighbour scaling
+    let x = (x as u64 * image.width() as u64 / output_dimensions.0.max(1) as u64) as usize;
+    let y = (y as u64 * image.height() as u64 / output_dimensions.1.max(1) as u64) as usize;
+    let idx = (y * image.width() as usize + x) * 4;
+    image.data()[idx..idx + 4].try_into().unwrap()
+}
+
+impl Background {
+    /// Fills `rect` of the frame, which is `output_dimensions` big, with the background.
+    pub(crate) fn fill(&self, rect: &Rect, output_dimensions: (u32, u32), frame: &mut [&mut [u8]]) {
+        let (width, height) = output_dimensions;
+        let Some(rect) = rect.intersection(&Rect::new(0, 0, width as i32, height as i32)) else {
+            return;
+        };
+
+        let to_bytes = |c: Srgba| -> [u8; 4] { c.into_format().into() };
+        let gradient = |from: &Srgba, to: &Srgba, i: i32, n: u32| {
+            let t = if n > 1 {
+                i as f32 / (n - 1) as f32
+            } else {
+                0f32
+            };
+            to_bytes(from.mix(*to, t))
+        };
+
+        for y in rect.y0..rect.y1 {
+            let row = frame_row(frame, width, y, rect.x0..rect.x1);
+            let pixels = frame[row].iter_mut().zip(rect.x0..);
+
+            match self {
+                Background::Solid(c) => {
+                    let rgba = to_bytes(*c);
+                    pixels.for_each(|(p, _)| p.copy_from_slice(&rgba));
+                }
+                Background::Transparent => pixels.for_each(|(p, _)| p.copy_from_slice(&[0; 4])),
+                Background::VerticalGradient { top, bottom } => {
+                    let rgba = gradient(top, bottom, y, height);
+                    pixels.for_each(|(p, _)| p.copy_from_slice(&rgba));
+                }
+                Background::HorizontalGradient { left, right } => {
+                    pixels.for_each(|(p, x)| p.copy_from_slice(&gradient(left, right, x, width)))
+                }
+                Background::Image(image) => pixels.for_each(|(p, x)| {
+                    p.copy_from_slice(&stretch(image, x as u32, y as u32, output_dimensions))
+                }),
+                Background::Keep => return,
+            }
+        }
+    }
+}
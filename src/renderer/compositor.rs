@@ -0,0 +1,164 @@
+use crate::drawing::Rect;
+use palette::{
+    blend::{Blend, Compose},
+    Srgba, WithAlpha,
+};
+
+use super::{Background, Renderer, World};
+
+/// Something that draws a whole frame on its own, like a `World`.
+pub trait Layer {
+    fn render(&self, frame: &mut [&mut [u8]]);
+    fn set_output_dimensions(&mut self, width: u32, height: u32);
+}
+
+impl<R: Renderer> Layer for World<R> {
+    fn render(&self, frame: &mut [&mut [u8]]) {
+        World::render(self, frame);
+    }
+
+    fn set_output_dimensions(&mut self, width: u32, height: u32) {
+        self.renderer.set_output_dimensions(width, height);
+    }
+}
+
+/// How a layer is combined with what is already in the frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Alpha blends the layer over the frame
+    #[default]
+    Normal,
+    /// Overwrites the frame, including its alpha
+    Replace,
+    Add,
+    Multiply,
+    Screen,
+}
+
+pub struct CompositeLayer {
+    pub layer: Box<dyn Layer>,
+    /// Part of the frame the layer is drawn into, `None` for the whole frame
+    pub viewport: Option<Rect>,
+    pub blend_mode: BlendMode,
+    /// Multiplies the alpha of every pixel of the layer
+    pub opacity: f32,
+    pub visible: bool,
+}
+
+impl CompositeLayer {
+    /// Fully opaque layer covering the whole frame.
+    pub fn new(layer: impl Layer + 'static) -> Self {
+        Self {
+            layer: Box::new(layer),
+            viewport: None,
+            blend_mode: BlendMode::Normal,
+            opacity: 1f32,
+            visible: true,
+        }
+    }
+
+    pub fn viewport(mut self, viewport: Rect) -> Self {
+        self.viewport = Some(viewport);
+        self
+    }
+
+    pub fn blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+}
+
+/// Draws an ordered stack of layers into one frame, the first layer is at the bottom.
+///
+/// Every layer is rendered into its own buffer the size of its viewport,
+/// so renderers of a layer should use a transparent background
+/// if whatever is below them has to show through.
+pub struct Compositor {
+    pub output_width: u32,
+    pub output_height: u32,
+    pub background: Background,
+    pub layers: Vec<CompositeLayer>,
+    buffer: Vec<u8>,
+}
+
+impl Compositor {
+    pub fn new(output_width: u32, output_height: u32) -> Self {
+        Self {
+            output_width,
+            output_height,
+            background: Background::default(),
+            layers: vec![],
+            buffer: vec![],
+        }
+    }
+
+    /// Puts `layer` on top of the stack and returns its index.
+    pub fn push(&mut self, layer: CompositeLayer) -> usize {
+        self.layers.push(layer);
+        self.layers.len() - 1
+    }
+
+    pub fn set_output_dimensions(&mut self, width: u32, height: u32) {
+        self.output_width = width;
+        self.output_height = height;
+    }
+
+    pub fn render(&mut self, frame: &mut [&mut [u8]]) {
+        let output_dimensions = (self.output_width, self.output_height);
+        let output = Rect::new(0, 0, self.output_width as i32, self.output_height as i32);
+        self.background.fill(&output, output_dimensions, frame);
+
+        for layer in self.layers.iter_mut().filter(|l| l.visible) {
+            let viewport = layer.viewport.unwrap_or(output);
+            let Some(visible) = viewport.intersection(&output) else {
+                continue;
+            };
+
+            // The layer renders its whole viewport, even the parts outside of the frame
+            let (width, height) = (viewport.width() as u32, viewport.height() as u32);
+            layer.layer.set_output_dimensions(width, height);
+            self.buffer.clear();
+            self.buffer.resize(width as usize * height as usize * 4, 0);
+            let mut layer_frame: Vec<&mut [u8]> = self.buffer.chunks_exact_mut(4).collect();
+            layer.layer.render(&mut layer_frame);
+
+            for y in visible.y0..visible.y1 {
+                for x in visible.x0..visible.x1 {
+                    let src =
+                        (y - viewport.y0) as usize * width as usize + (x - viewport.x0) as usize;
+                    let dest = y as usize * self.output_width as usize + x as usize;
+                    if dest >= frame.len() {
+                        continue;
+                    }
+                    let rgba = blend(
+                        layer_frame[src],
+                        frame[dest],
+                        layer.blend_mode,
+                        layer.opacity,
+                    );
+                    frame[dest].copy_from_slice(&rgba);
+                }
+            }
+        }
+    }
+}
+
+fn blend(src: &[u8], dest: &[u8], mode: BlendMode, opacity: f32) -> [u8; 4] {
+    let src: Srgba<f32> = Srgba::new(src[0], src[1], src[2], src[3]).into_format();
+    let src = src.with_alpha(src.alpha * opacity.clamp(0f32, 1f32));
+    let dest: Srgba<f32> = Srgba::new(dest[0], dest[1], dest[2], dest[3]).into_format();
+
+    let out = match mode {
+        BlendMode::Normal => src.over(dest),
+        BlendMode::Replace => src,
+        BlendMode::Add => src.plus(dest),
+        BlendMode::Multiply => src.multiply(dest),
+        BlendMode::Screen => src.screen(dest),
+    };
+    out.into_format().into()
+}
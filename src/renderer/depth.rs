@@ -0,0 +1,124 @@
+use super::frame_row;
+use crate::{camera::Camera, drawing::Rect};
+use palette::{FromColor, Hsv, Srgb};
+
+/// Depth of the closest fragment at every pixel of the output.
+///
+/// Values are the ones compared by the depth test, in [-1; 1] between the near and far planes.
+/// Pixels nothing was drawn to hold `f32::INFINITY`.
+#[derive(Debug, Clone)]
+pub struct DepthBuffer {
+    width: u32,
+    height: u32,
+    pub(crate) values: Vec<f32>,
+}
+
+impl DepthBuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            values: vec![f32::INFINITY; width as usize * height as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Raw depth values row by row
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    /// Depth at pixel (`x`, `y`), `None` if nothing was drawn there.
+    pub fn get(&self, x: u32, y: u32) -> Option<f32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let depth = self.values[y as usize * self.width as usize + x as usize];
+        depth.is_finite().then_some(depth)
+    }
+
+    /// Distance in front of `camera` of the fragment at pixel (`x`, `y`).
+    pub fn linear_depth(&self, x: u32, y: u32, camera: &Camera) -> Option<f32> {
+        self.get(x, y).map(|d| camera.linear_depth(d))
+    }
+
+    /// Distances in front of `camera` of every pixel, `f32::INFINITY` where nothing was drawn.
+    pub fn linearized(&self, camera: &Camera) -> Vec<f32> {
+        self.values
+            .iter()
+            .map(|d| {
+                if d.is_finite() {
+                    camera.linear_depth(*d)
+                } else {
+                    f32::INFINITY
+                }
+            })
+            .collect()
+    }
+
+    pub(crate) fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.values.clear();
+        self.values
+            .resize(width as usize * height as usize, f32::INFINITY);
+    }
+
+    /// Resets `rect` to hold no fragments.
+    pub(crate) fn clear(&mut self, rect: &Rect) {
+        for y in rect.y0..rect.y1 {
+            let row = y as usize * self.width as usize;
+            self.values[row + rect.x0 as usize..row + rect.x1 as usize].fill(f32::INFINITY);
+        }
+    }
+}
+
+/// What the rasterizer writes into the frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RenderMode {
+    /// Colors of the meshes
+    #[default]
+    Shaded,
+    /// Linear depth from white at the near plane to black at the far one
+    DepthGrayscale,
+    /// Linear depth from red at the near plane through green to blue at the far one
+    DepthFalseColor,
+}
+
+impl RenderMode {
+    /// Overwrites every pixel of `rect` that has a depth with its visualization.
+    pub(crate) fn write_depth(
+        &self,
+        camera: &Camera,
+        rect: &Rect,
+        depth_buffer: &DepthBuffer,
+        frame: &mut [&mut [u8]],
+    ) {
+        for y in rect.y0..rect.y1 {
+            let row = frame_row(frame, depth_buffer.width, y, rect.x0..rect.x1);
+            for (x, pixel) in (rect.x0..).zip(&mut frame[row]) {
+                let Some(distance) = depth_buffer.linear_depth(x as u32, y as u32, camera) else {
+                    continue;
+                };
+                let t = ((distance - camera.near) / (camera.far - camera.near)).clamp(0f32, 1f32);
+                let rgb = match self {
+                    RenderMode::Shaded => return,
+                    RenderMode::DepthGrayscale => Srgb::new(1f32 - t, 1f32 - t, 1f32 - t),
+                    RenderMode::DepthFalseColor => {
+                        Srgb::from_color(Hsv::new(240f32 * t, 1f32, 1f32))
+                    }
+                };
+                let rgb: [u8; 3] = rgb.into_format().into();
+
+                pixel.copy_from_slice(&[rgb[0], rgb[1], rgb[2], 0xff]);
+            }
+        }
+    }
+}
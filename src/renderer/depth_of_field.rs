@@ -0,0 +1,125 @@
+use crate::{
+    camera::{Camera, Projection},
+    drawing::Rect,
+    renderer::frame_row,
+};
+
+/// Thin lens depth of field applied after rasterization.
+///
+/// The blur follows the physical parameters of the camera:
+/// `focal_length` and `aperture` are in millimeters and world units are treated as meters.
+/// Orthographic cameras have no lens and are left sharp.
+#[derive(Debug, Clone, Copy)]
+pub struct DepthOfField {
+    /// Focal length divided by the diameter of the lens opening
+    pub f_stop: f32,
+    /// Distance to the plane in perfect focus
+    pub focus_distance: f32,
+    /// Largest blur radius in pixels, bounds the cost of the effect
+    pub max_radius: u32,
+}
+
+impl Default for DepthOfField {
+    fn default() -> Self {
+        Self {
+            f_stop: 2.8f32,
+            focus_distance: 2f32,
+            max_radius: 6,
+        }
+    }
+}
+
+impl DepthOfField {
+    /// Diameter in pixels of the circle a point `distance` away is blurred into.
+    pub fn circle_of_confusion(
+        &self,
+        camera: &Camera,
+        distance: f32,
+        output_dimensions: (u32, u32),
+    ) -> f32 {
+        if camera.projection != Projection::Perspective {
+            return 0f32;
+        }
+        let focal_length = camera.focal_length / 1000f32;
+        let focus = self.focus_distance.max(focal_length * 1.001f32);
+        let lens_diameter = focal_length / self.f_stop;
+        // Size of the circle on the film
+        let coc = lens_diameter * (distance - focus).abs() / distance * focal_length
+            / (focus - focal_length);
+
+        // Part of the film that is actually visible after fitting the aperture to the output
+        let canvas = camera.canvas(output_dimensions);
+        let film_width = canvas.width / camera.near * focal_length;
+        coc / film_width * output_dimensions.0 as f32
+    }
+
+    /// Blurs `rect` of the frame according to the depth of every pixel.
+    /// `frame` and `depth_buffer` are `output_width` pixels wide.
+    pub(crate) fn apply(
+        &self,
+        camera: &Camera,
+        rect: &Rect,
+        clip: &Rect,
+        output_width: u32,
+        depth_buffer: &[f32],
+        frame: &mut [&mut [u8]],
+    ) {
+        let dimensions = (rect.width() as u32, rect.height() as u32);
+        let (width, height) = (clip.width() as usize, clip.height() as usize);
+        let index = |x: i32, y: i32| y as usize * output_width as usize + x as usize;
+        let last_row = frame_row(frame, output_width, clip.y1 - 1, clip.x0..clip.x1);
+        if clip.is_empty() || last_row.len() < width {
+            return;
+        }
+
+        // Blur radius and color of every pixel of the clipped region
+        let mut radii = Vec::with_capacity(width * height);
+        let mut colors = Vec::with_capacity(width * height);
+        for y in clip.y0..clip.y1 {
+            for x in clip.x0..clip.x1 {
+                let depth = depth_buffer[index(x, y)];
+                // Background counts as being on the far plane
+                let distance = if depth.is_finite() {
+                    camera.linear_depth(depth)
+                } else {
+                    camera.far
+                };
+                let coc = self.circle_of_confusion(camera, distance, dimensions);
+                radii.push((coc / 2f32).min(self.max_radius as f32));
+                let p = &frame[index(x, y)];
+                colors.push([p[0] as f32, p[1] as f32, p[2] as f32, p[3] as f32]);
+            }
+        }
+
+        // Every pixel spreads over its circle of confusion, so a pixel gathers
+        // the neighbours whose circles reach it
+        let r = self.max_radius as i32;
+        for y in 0..height as i32 {
+            for x in 0..width as i32 {
+                let mut sum = [0f32; 4];
+                let mut weight = 0f32;
+                for sy in (y - r).max(0)..(y + r + 1).min(height as i32) {
+                    for sx in (x - r).max(0)..(x + r + 1).min(width as i32) {
+                        let s = sy as usize * width + sx as usize;
+                        let distance = (((sx - x).pow(2) + (sy - y).pow(2)) as f32).sqrt();
+                        let radius = radii[s];
+                        if distance > radius && (sx, sy) != (x, y) {
+                            continue;
+                        }
+                        // Spread the color evenly over the area of the circle
+                        let w = 1f32 / (radius * radius).max(1f32);
+                        for c in 0..4 {
+                            sum[c] += colors[s][c] * w;
+                        }
+                        weight += w;
+                    }
+                }
+
+                let p = &mut frame[index(x + clip.x0, y + clip.y0)];
+                for c in 0..4 {
+                    p[c] = (sum[c] / weight).round() as u8;
+                }
+            }
+        }
+    }
+}
@@ -1,12 +1,156 @@
-use crate::drawing::Shape2D;
-use palette::{blend::Compose, Srgba};
+use crate::drawing::{Rect, Shape2D};
+use palette::{blend::Compose, Srgba, WithAlpha};
 
-use super::Renderer;
+use super::{frame_row, Background, Renderer, Scene2D};
 
 /// If a rendrer renders why doesn't a drawer draw?
 pub struct Drawifier {
     pub output_width: u32,
     pub output_height: u32,
+    pub background: Background,
+}
+
+impl Drawifier {
+    /// Blends `shape` over the contents of `frame`.
+    pub fn draw(&self, shape: &Shape2D, frame: &mut [&mut [u8]]) {
+        self.draw_clipped(shape, &self.output_rect(), frame);
+    }
+
+    /// Clears the frame and draws the visible shapes of `scene`.
+    pub fn render_scene(&self, scene: &Scene2D, frame: &mut [&mut [u8]]) {
+        self.clear(&self.output_rect(), frame);
+        for shape in scene.shapes() {
+            self.draw(shape, frame);
+        }
+    }
+
+    /// Repaints only the parts of the frame changed since the previous call
+    /// and returns the repainted rectangles.
+    ///
+    /// `frame` has to hold the result of the previous render of the same scene.
+    /// Call `Scene2D::invalidate` whenever that is not the case, e.g. after a resize.
+    ///
+    /// With `Background::Keep` the scene keeps a copy of the frame it was first drawn over,
+    /// or drawn over after `Scene2D::invalidate`, and restores it where shapes moved away.
+    pub fn render_dirty(&self, scene: &mut Scene2D, frame: &mut [&mut [u8]]) -> Vec<Rect> {
+        let output = self.output_rect();
+        let keep = matches!(self.background, Background::Keep);
+        let dirty = if scene.take_invalidated() {
+            if keep {
+                scene.kept = frame.iter().flat_map(|p| p.iter().copied()).collect();
+            }
+            scene.take_damage();
+            vec![output]
+        } else {
+            merge_rects(
+                scene
+                    .take_damage()
+                    .iter()
+                    .filter_map(|r| r.intersection(&output))
+                    .collect(),
+            )
+        };
+
+        for rect in &dirty {
+            if keep {
+                self.restore(rect, &scene.kept, frame);
+            } else {
+                self.clear(rect, frame);
+            }
+            for shape in scene.shapes_in(rect) {
+                self.draw_clipped(shape, rect, frame);
+            }
+        }
+        dirty
+    }
+
+    fn output_rect(&self) -> Rect {
+        Rect::new(0, 0, self.output_width as i32, self.output_height as i32)
+    }
+
+    pub(crate) fn draw_clipped(&self, shape: &Shape2D, clip: &Rect, frame: &mut [&mut [u8]]) {
+        match shape {
+            Shape2D::Pixel(p) => self.blend_span(p.y, p.x, p.x + 1, p.color, clip, frame),
+            Shape2D::Spans { paint, spans } => {
+                for s in spans {
+                    let color = paint.with_alpha(paint.alpha * s.coverage);
+                    self.blend_span(s.y, s.x_start, s.x_end, color, clip, frame);
+                }
+            }
+            Shape2D::Compound(shapes) => {
+                for s in shapes {
+                    self.draw_clipped(s, clip, frame);
+                }
+            }
+        }
+    }
+
+    fn clear(&self, rect: &Rect, frame: &mut [&mut [u8]]) {
+        self.background
+            .fill(rect, (self.output_width, self.output_height), frame);
+    }
+
+    /// Copies `rect` of `kept`, an earlier copy of the frame, back into the frame.
+    fn restore(&self, rect: &Rect, kept: &[u8], frame: &mut [&mut [u8]]) {
+        for y in rect.y0..rect.y1 {
+            let start = self.output_width as usize * y.max(0) as usize + rect.x0.max(0) as usize;
+            let kept = kept.chunks_exact(4).skip(start);
+            for (pixel, kept) in self.row(y, rect.x0, rect.x1, frame).iter_mut().zip(kept) {
+                pixel.copy_from_slice(kept);
+            }
+        }
+    }
+
+    fn blend_span(
+        &self,
+        y: i32,
+        x_start: i32,
+        x_end: i32,
+        src: Srgba,
+        clip: &Rect,
+        frame: &mut [&mut [u8]],
+    ) {
+        // Skip unrenderable pixels
+        if !(clip.y0..clip.y1).contains(&y) {
+            return;
+        }
+        for pixel in self.row(y, x_start.max(clip.x0), x_end.min(clip.x1), frame) {
+            let dest: Srgba<f32> = Srgba::new(pixel[0], pixel[1], pixel[2], pixel[3]).into_format();
+            let dest: [u8; 4] = src.over(dest).into_format().into();
+            pixel.copy_from_slice(&dest);
+        }
+    }
+
+    /// Pixels `x_start..x_end` of row `y`, clamped to the frame.
+    fn row<'a, 'b>(
+        &self,
+        y: i32,
+        x_start: i32,
+        x_end: i32,
+        frame: &'a mut [&'b mut [u8]],
+    ) -> &'a mut [&'b mut [u8]] {
+        let x_start = x_start.max(0);
+        let x_end = x_end.min(self.output_width as i32);
+        if x_start >= x_end || !(0..self.output_height as i32).contains(&y) {
+            return &mut [];
+        }
+
+        let row = frame_row(frame, self.output_width, y, x_start..x_end);
+        &mut frame[row]
+    }
+}
+
+/// Merges overlapping rectangles until none of them overlap.
+fn merge_rects(mut rects: Vec<Rect>) -> Vec<Rect> {
+    let mut merged: Vec<Rect> = Vec::with_capacity(rects.len());
+    while let Some(mut rect) = rects.pop() {
+        // Growing a rectangle can make it overlap ones that were checked before
+        while let Some(i) = merged.iter().position(|m| m.intersection(&rect).is_some()) {
+            rect = rect.union(&merged.swap_remove(i));
+        }
+        merged.push(rect);
+    }
+    merged
 }
 
 impl Renderer for Drawifier {
@@ -18,30 +162,9 @@ impl Renderer for Drawifier {
         objects: &[Self::Renderable],
         frame: &mut [&mut [u8]],
     ) {
-        for pixel in &mut *frame {
-            let rgba = [0, 0, 0, 0xff];
-            pixel.copy_from_slice(&rgba);
-        }
-
-        for p in objects.iter().flat_map(|e| e.clone().into_iter()) {
-            // Skip unrenderable pixels
-            if !(0..self.output_width as i32).contains(&p.x)
-                || !(0..self.output_height as i32).contains(&p.y)
-            {
-                continue;
-            }
-            let idx = self.output_width as usize * p.y as usize + p.x as usize;
-            if idx >= frame.len() {
-                // Indices go out of bounds only if Wu's line endpoints lie directly in the
-                // bottom right corner. Hightly unlikely to happen often so we can just ignore
-                // them.
-                continue;
-            }
-            let dest = &frame[idx];
-            let dest: Srgba<f32> = Srgba::new(dest[0], dest[1], dest[2], dest[3]).into_format();
-            let src = p.color;
-            let dest: [u8; 4] = src.over(dest).into_format().into();
-            frame[idx].copy_from_slice(&dest);
+        self.clear(&self.output_rect(), frame);
+        for shape in objects {
+            self.draw(shape, frame);
         }
     }
 
@@ -0,0 +1,106 @@
+use glam::Vec2;
+
+use crate::drawing::Rect;
+
+/// Raster coordinates are snapped to 1/256 of a pixel
+const SUBPIXEL_BITS: u32 = 8;
+const SUBPIXEL: i64 = 1 << SUBPIXEL_BITS;
+/// Vertices further out than this many pixels are rejected to keep edge functions in range
+const GUARD_BAND: f32 = (1 << 20) as f32;
+
+/// Edge function of the edge `a -> b` stepped across the raster.
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    /// Change of the edge function per pixel to the right
+    step_x: i64,
+    /// Change of the edge function per pixel down
+    step_y: i64,
+    /// Points exactly on top-left edges belong to the triangle, the rest are left to neighbours
+    bias: i64,
+}
+
+impl Edge {
+    fn new(a: (i64, i64), b: (i64, i64)) -> Self {
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        // With y pointing down and counter-clockwise triangles
+        // a top edge is horizontal and goes left, a left edge goes down
+        let top_left = (dy == 0 && dx < 0) || dy > 0;
+        Self {
+            step_x: dy * SUBPIXEL,
+            step_y: -dx * SUBPIXEL,
+            bias: if top_left { 0 } else { -1 },
+        }
+    }
+
+    /// Twice the signed area of the triangle `a`, `b`, `p`,
+    /// positive if it is counter-clockwise on the screen
+    fn at(a: (i64, i64), b: (i64, i64), p: (i64, i64)) -> i64 {
+        (b.1 - a.1) * (p.0 - a.0) - (b.0 - a.0) * (p.1 - a.1)
+    }
+}
+
+fn snap(p: Vec2) -> Option<(i64, i64)> {
+    if !p.is_finite() || p.x.abs() > GUARD_BAND || p.y.abs() > GUARD_BAND {
+        return None;
+    }
+    let s = SUBPIXEL as f32;
+    Some(((p.x * s).round() as i64, (p.y * s).round() as i64))
+}
+
+/// Calls `fragment` with the pixel coordinates and barycentric weights
+/// of every pixel inside `clip` whose center is covered by the triangle.
+///
+/// Vertices are snapped to a fixed point grid and pixels on shared edges follow
+/// the top-left rule, so adjacent triangles cover every pixel exactly once.
+/// Clockwise triangles face away from the camera and are culled.
+pub(crate) fn triangle(p: [Vec2; 3], clip: &Rect, mut fragment: impl FnMut(i32, i32, [f32; 3])) {
+    let (Some(v0), Some(v1), Some(v2)) = (snap(p[0]), snap(p[1]), snap(p[2])) else {
+        return;
+    };
+    let area = Edge::at(v0, v1, v2);
+    if area <= 0 {
+        return;
+    }
+
+    // Bounding box in whole pixels
+    let min_x = v0.0.min(v1.0).min(v2.0) >> SUBPIXEL_BITS;
+    let min_y = v0.1.min(v1.1).min(v2.1) >> SUBPIXEL_BITS;
+    let max_x = (v0.0.max(v1.0).max(v2.0) >> SUBPIXEL_BITS) + 1;
+    let max_y = (v0.1.max(v1.1).max(v2.1) >> SUBPIXEL_BITS) + 1;
+    let x0 = min_x.max(clip.x0 as i64);
+    let y0 = min_y.max(clip.y0 as i64);
+    let x1 = max_x.min(clip.x1 as i64);
+    let y1 = max_y.min(clip.y1 as i64);
+    if x0 >= x1 || y0 >= y1 {
+        return;
+    }
+
+    let edges = [Edge::new(v1, v2), Edge::new(v2, v0), Edge::new(v0, v1)];
+    // Edge functions at the center of the first pixel
+    let start = (x0 * SUBPIXEL + SUBPIXEL / 2, y0 * SUBPIXEL + SUBPIXEL / 2);
+    let mut row = [
+        Edge::at(v1, v2, start),
+        Edge::at(v2, v0, start),
+        Edge::at(v0, v1, start),
+    ];
+
+    let area = area as f32;
+    for y in y0..y1 {
+        let mut w = row;
+        for x in x0..x1 {
+            if (0..3).all(|i| w[i] + edges[i].bias >= 0) {
+                fragment(
+                    x as i32,
+                    y as i32,
+                    [w[0] as f32 / area, w[1] as f32 / area, w[2] as f32 / area],
+                );
+            }
+            for i in 0..3 {
+                w[i] += edges[i].step_x;
+            }
+        }
+        for i in 0..3 {
+            row[i] += edges[i].step_y;
+        }
+    }
+}
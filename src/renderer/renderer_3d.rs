@@ -1,185 +1,717 @@
 use crate::{
-    camera::Camera,
-    drawing::{LineBuilder, Pixel, Shape2D, WuLine},
-    renderer::{Drawifier, Renderer},
+    camera::{Camera, Projection},
+    drawing::{LineBuilder, Rect, Shape2D, WuLine},
+    formats::Texture,
+    renderer::{
+        frame_row, raster, transparency::Accumulator, vertex::VaryingLayout, AttributeValues,
+        Background, DepthBuffer, DepthOfField, Drawifier, Fragment, FragmentShader, RenderMode,
+        Renderer, Semantic, Transparency, VertexAttribute,
+    },
 };
-use glam::{Mat4, Vec3, Vec4};
-use itertools::Itertools;
-use palette::{Srgb, Srgba};
+use glam::{Vec3, Vec4};
+use palette::{blend::Compose, Srgba};
+use rayon::prelude::*;
+use std::iter;
 
-#[derive(Debug, Clone, Copy)]
-pub struct VertexAttribute {
-    pub color: Srgb,
-}
-
-pub trait Mesh3D {
+/// Geometry the `Rasterizer` can draw.
+///
+/// Buffers are borrowed from the mesh, so drawing a mesh every frame does not copy it.
+pub trait Mesh3D: Sync {
     /// An array of vertices
-    fn vertices(&self) -> Vec<Vec3>;
-    /// An array of triangles, formed by vertices with indices in tuples
-    fn indices(&self) -> Vec<(usize, usize, usize)>;
-    /// An array of vertex attributes.
-    /// Each element corresponds to a vertex in `vertices()`
-    fn attributes(&self) -> Vec<VertexAttribute>;
+    fn vertices(&self) -> &[Vec3];
+    /// An array of triangles, formed by vertices with indices in tuples.
+    /// Triangles face the camera when their vertices are counter-clockwise on the screen,
+    /// like in OBJ and glTF files, the others are culled.
+    fn indices(&self) -> &[(usize, usize, usize)];
+    /// Per-vertex data besides the positions, such as normals, texture coordinates and colors.
+    /// Attributes with fewer values than there are vertices are ignored, vertices are white
+    /// without a `Semantic::Color` attribute.
+    /// They are pushed onto `attributes`, so a buffer can be reused for every mesh.
+    fn attributes<'a>(&'a self, attributes: &mut Vec<VertexAttribute<'a>>);
+    /// Image multiplied with the colors of the fragments, sampled at the first set of texture
+    /// coordinates. Meshes without those are not textured.
+    fn texture(&self) -> Option<&Texture> {
+        None
+    }
 }
 
 pub struct Rasterizer {
     pub output_width: u32,
     pub output_height: u32,
+    /// Outlines every triangle without depth testing. The outlines of all objects are drawn
+    /// after the polygons of all objects, so no polygon covers an outline.
     pub show_wireframe: bool,
     pub show_polygons: bool,
+    pub background: Background,
+    /// Blurs the image according to the camera lens, `None` keeps everything sharp
+    pub depth_of_field: Option<DepthOfField>,
+    pub render_mode: RenderMode,
+    pub transparency: Transparency,
+    /// Colors the fragments from all attributes of the meshes, `None` uses their colors
+    pub fragment_shader: Option<FragmentShader>,
 }
 
-impl Renderer for Rasterizer {
-    type Renderable = Box<dyn Mesh3D>;
+/// The closest triangle at a pixel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pick {
+    /// Index of the mesh in the rendered objects
+    pub object_index: usize,
+    /// Index of the triangle in `Mesh3D::indices`
+    pub triangle_index: usize,
+    /// Weights of the triangle vertices at the pixel, corrected for perspective like the
+    /// attributes handed to the fragment shader
+    pub barycentrics: Vec3,
+    /// Same value the depth test compares
+    pub depth: f32,
+}
 
-    fn render(&self, camera: &Camera, objects: &[Self::Renderable], frame: &mut [&mut [u8]]) {
-        let canvas = camera.canvas((self.output_width, self.output_height));
-        let world_to_camera = camera.world_to_camera();
-
-        let perspective = Mat4::from_cols(
-            Vec4::X * 2f32 * camera.near / canvas.width,
-            Vec4::Y * 2f32 * camera.near / canvas.height,
-            Vec4::NEG_Z * (camera.far + camera.near) / (camera.far - camera.near) + Vec4::NEG_W,
-            Vec4::NEG_Z * 2f32 * camera.far * camera.near / (camera.far - camera.near),
+/// Records the closest triangle at every pixel of the output.
+#[derive(Debug, Clone)]
+pub struct IdBuffer {
+    width: u32,
+    height: u32,
+    entries: Vec<Option<Pick>>,
+}
+
+impl IdBuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            entries: vec![None; width as usize * height as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Triangle drawn at pixel (`x`, `y`), `None` if there is nothing there.
+    pub fn query(&self, x: u32, y: u32) -> Option<Pick> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.entries[y as usize * self.width as usize + x as usize]
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.entries.clear();
+        self.entries.resize(width as usize * height as usize, None);
+    }
+}
+
+/// A camera drawing into a part of the frame.
+#[derive(Debug, Clone, Copy)]
+pub struct Viewport {
+    pub camera: Camera,
+    /// Part of the frame the camera is fit into according to its `FitStrategy`
+    pub rect: Rect,
+}
+
+impl Rasterizer {
+    /// Draws shaded polygons of `output_width` by `output_height` pixels over a black background,
+    /// without wireframes, depth of field or a fragment shader.
+    pub fn new(output_width: u32, output_height: u32) -> Self {
+        Self {
+            output_width,
+            output_height,
+            show_wireframe: false,
+            show_polygons: true,
+            background: Default::default(),
+            depth_of_field: None,
+            render_mode: Default::default(),
+            transparency: Default::default(),
+            fragment_shader: None,
+        }
+    }
+
+    /// Draws `objects` once for every viewport.
+    /// Each viewport only writes to its own part of the frame and of the depth buffer,
+    /// the rest of the frame is left as is, without a background.
+    pub fn render_viewports(
+        &self,
+        viewports: &[Viewport],
+        objects: &[Box<dyn Mesh3D>],
+        frame: &mut [&mut [u8]],
+    ) {
+        self.render_targets(viewports, objects, Some(frame), None, None);
+    }
+
+    /// Draws `objects` and keeps the depth of every pixel in `depth`.
+    pub fn render_with_depth(
+        &self,
+        camera: &Camera,
+        objects: &[Box<dyn Mesh3D>],
+        frame: &mut [&mut [u8]],
+        depth: &mut DepthBuffer,
+    ) {
+        self.render_targets(
+            &[self.full_viewport(camera)],
+            objects,
+            Some(frame),
+            Some(depth),
+            None,
         );
+    }
 
-        let mut depth_buffer =
-            vec![f32::INFINITY; self.output_width as usize * self.output_height as usize];
-        let shapes = objects
-            .iter()
-            .flat_map(|o| {
-                let points = o
-                    .vertices()
-                    .iter()
-                    .map(|v| {
-                        // Note: this is old version of the uncommented code below
-                        // this does not use matrices but reaches the same result
-                        // // Project points onto the canvas
-                        // let x_screen = (v.x / (-v.z)) * camera.near;
-                        // let y_screen = (v.y / (-v.z)) * camera.near;
-                        // println!("Screen space: {x_screen}, {y_screen}");
-                        // // Remap points into NDC (Normalized Device Coordinates) space [-1; 1].
-                        // let x_ndc = (2f32 * v.x) / canvas.width;
-                        // let y_ndc = (2f32 * v.y) / canvas.height;
-                        // println!("NDC: {x_ndc}, {y_ndc}");
-
-                        // Important: point is now in homogenous coordinates
-                        let v = world_to_camera * Vec4::from((*v, 1f32));
-                        // Apply projection, this also squishes z into [0; 1]
-                        let v = perspective * v;
-                        // Transform back from homogenous coordinates
-                        let v = Vec3::new(v.x / v.w, v.y / v.w, v.z / v.w);
-                        // Project normalized coordinates to raster space
-                        let x_raster = ((v.x + 1f32) / 2f32 * self.output_width as f32) as i32;
-                        // Y is down in raster space but up in NDC, so invert it
-                        let y_raster = ((1f32 - v.y) / 2f32 * self.output_height as f32) as i32;
-                        // Keep z coordinate for z-buffering
-                        Vec3::new(x_raster as f32, y_raster as f32, v.z)
-                    })
-                    .collect::<Vec<_>>();
-
-                let attributes = o.attributes();
-
-                let planes = if self.show_polygons {
-                    o.indices()
-                        .iter()
-                        .flat_map(|t| {
-                            let p0 = points[t.0];
-                            let p1 = points[t.1];
-                            let p2 = points[t.2];
-
-                            let a0 = attributes[t.0];
-                            let a1 = attributes[t.1];
-                            let a2 = attributes[t.2];
-
-                            let min = (p0.x.min(p1.x.min(p2.x)), p0.y.min(p1.y.min(p2.y)));
-
-                            let max = (p0.x.max(p1.x.max(p2.x)), p0.y.max(p1.y.max(p2.y)));
-
-                            (min.0 as i32..max.0 as i32)
-                                .cartesian_product(min.1 as i32..max.1 as i32)
-                                .filter(|(x, y)| {
-                                    (*x as u32) < self.output_width
-                                        && (*y as u32) < self.output_height
-                                })
-                                .flat_map(|(x, y)| {
-                                    let (x, y) = (x as f32, y as f32);
-                                    let area =
-                                        edge_function((p0.x, p0.y), (p1.x, p1.y), (p2.x, p2.y));
-                                    let w0 = edge_function((p1.x, p1.y), (p2.x, p2.y), (x, y));
-                                    let w1 = edge_function((p2.x, p2.y), (p0.x, p0.y), (x, y));
-                                    let w2 = edge_function((p0.x, p0.y), (p1.x, p1.y), (x, y));
-                                    if w0 >= 0f32 && w1 >= 0f32 && w2 >= 0f32 {
-                                        // Pixel does overlap the triangle
-                                        let w0 = w0 / area;
-                                        let w1 = w1 / area;
-                                        let w2 = w2 / area;
-
-                                        let z = 1f32
-                                            / (1f32 / p0.z * w0
-                                                + 1f32 / p1.z * w1
-                                                + 1f32 / p2.z * w2);
-                                        let idx =
-                                            y as usize * self.output_width as usize + x as usize;
-                                        if z < depth_buffer[idx] {
-                                            depth_buffer[idx] = z;
-                                            let c0 = a0.color;
-                                            let c1 = a1.color;
-                                            let c2 = a2.color;
-
-                                            let r = w0 * c0.red + w1 * c1.red + w2 * c2.red;
-                                            let g = w0 * c0.green + w1 * c1.green + w2 * c2.green;
-                                            let b = w0 * c0.blue + w1 * c1.blue + w2 * c2.blue;
-
-                                            // Multiply by z to achieve perspective correct
-                                            // interpolation of color attributes.
-                                            let c = Srgba::new(r * z, g * z, b * z, 1f32);
-                                            Some(Shape2D::Pixel(Pixel {
-                                                x: x as i32,
-                                                y: y as i32,
-                                                color: c,
-                                            }))
-                                        } else {
-                                            None
-                                        }
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .collect_vec()
-                        })
-                        .collect_vec()
-                } else {
-                    vec![]
-                };
+    /// Finds the depth of every pixel without drawing anything.
+    pub fn depth_buffer(&self, camera: &Camera, objects: &[Box<dyn Mesh3D>]) -> DepthBuffer {
+        let mut depth = DepthBuffer::new(self.output_width, self.output_height);
+        self.render_targets(
+            &[self.full_viewport(camera)],
+            objects,
+            None,
+            Some(&mut depth),
+            None,
+        );
+        depth
+    }
 
-                let lines = if self.show_wireframe {
-                    o.indices()
-                        .iter()
-                        .map(|t| {
-                            LineBuilder::<WuLine>::new()
-                                .color(Srgba::new(0.7f32, 0.5f32, 0.6f32, 1f32))
-                                .from((points[t.0].x as i32, points[t.0].y as i32))
-                                .to((points[t.1].x as i32, points[t.1].y as i32))
-                                .to((points[t.2].x as i32, points[t.2].y as i32))
-                                .close()
-                                .shape()
-                        })
-                        .collect_vec()
-                } else {
-                    vec![]
-                };
+    /// Draws `objects` and records which triangle ended up at every pixel in `ids`.
+    pub fn render_with_ids(
+        &self,
+        camera: &Camera,
+        objects: &[Box<dyn Mesh3D>],
+        frame: &mut [&mut [u8]],
+        ids: &mut IdBuffer,
+    ) {
+        self.render_targets(
+            &[self.full_viewport(camera)],
+            objects,
+            Some(frame),
+            None,
+            Some(ids),
+        );
+    }
 
-                [planes, lines]
-            })
-            .flatten()
-            .collect_vec();
+    /// Finds the triangle visible at every pixel without drawing anything.
+    pub fn pick_buffer(&self, camera: &Camera, objects: &[Box<dyn Mesh3D>]) -> IdBuffer {
+        let mut ids = IdBuffer::new(self.output_width, self.output_height);
+        self.render_targets(
+            &[self.full_viewport(camera)],
+            objects,
+            None,
+            None,
+            Some(&mut ids),
+        );
+        ids
+    }
+
+    fn full_viewport(&self, camera: &Camera) -> Viewport {
+        Viewport {
+            camera: *camera,
+            rect: Rect::new(0, 0, self.output_width as i32, self.output_height as i32),
+        }
+    }
+
+    fn render_targets(
+        &self,
+        viewports: &[Viewport],
+        objects: &[Box<dyn Mesh3D>],
+        mut frame: Option<&mut [&mut [u8]]>,
+        depth: Option<&mut DepthBuffer>,
+        mut ids: Option<&mut IdBuffer>,
+    ) {
+        let output_dimensions = (self.output_width, self.output_height);
+        let output = Rect::new(0, 0, self.output_width as i32, self.output_height as i32);
+        if let Some(ids) = ids.as_deref_mut() {
+            ids.resize(self.output_width, self.output_height);
+        }
 
         let d = Drawifier {
             output_width: self.output_width,
             output_height: self.output_height,
+            background: Background::Keep,
+        };
+        let mut local_depth;
+        let depth_buffer = match depth {
+            Some(depth) => depth,
+            None => {
+                local_depth = DepthBuffer::new(self.output_width, self.output_height);
+                &mut local_depth
+            }
+        };
+        depth_buffer.resize(self.output_width, self.output_height);
+
+        for viewport in viewports {
+            let Some(clip) = viewport.rect.intersection(&output) else {
+                continue;
+            };
+            // Viewports may overlap, the later one starts with clean buffers
+            if let Some(frame) = frame.as_deref_mut() {
+                self.background.fill(&clip, output_dimensions, frame);
+            }
+            depth_buffer.clear(&clip);
+            if let Some(ids) = ids.as_deref_mut() {
+                for y in clip.y0..clip.y1 {
+                    let row = y as usize * self.output_width as usize;
+                    ids.entries[row + clip.x0 as usize..row + clip.x1 as usize].fill(None);
+                }
+            }
+
+            let shaded = self.render_mode == RenderMode::Shaded;
+            let wireframes = self.rasterize(
+                viewport,
+                objects,
+                &clip,
+                &mut depth_buffer.values,
+                ids.as_deref_mut(),
+                frame.as_deref_mut().filter(|_| shaded),
+            );
+            let Some(frame) = frame.as_deref_mut() else {
+                continue;
+            };
+            if !shaded {
+                self.render_mode
+                    .write_depth(&viewport.camera, &clip, depth_buffer, frame);
+                continue;
+            }
+            for shape in &wireframes {
+                d.draw_clipped(shape, &clip, frame);
+            }
+            if let Some(dof) = &self.depth_of_field {
+                dof.apply(
+                    &viewport.camera,
+                    &viewport.rect,
+                    &clip,
+                    self.output_width,
+                    &depth_buffer.values,
+                    frame,
+                );
+            }
+        }
+    }
+
+    /// Projects `objects` into the viewport and writes the fragments that pass the depth test
+    /// inside of `clip` straight into `frame`. Fragments that pass the test are also recorded
+    /// in `ids`. Returns the wireframes, which are not depth tested.
+    ///
+    /// Triangles are binned into tiles of the clipped region and the tiles are shaded in parallel.
+    /// Every tile draws its triangles in submission order, so the result does not depend
+    /// on how the tiles are scheduled. Wireframes are drawn over the polygons of every object.
+    fn rasterize(
+        &self,
+        viewport: &Viewport,
+        objects: &[Box<dyn Mesh3D>],
+        clip: &Rect,
+        depth_buffer: &mut [f32],
+        mut ids: Option<&mut IdBuffer>,
+        mut frame: Option<&mut [&mut [u8]]>,
+    ) -> Vec<Shape2D> {
+        let Viewport { camera, rect } = viewport;
+        let (width, height) = (rect.width() as u32, rect.height() as u32);
+
+        // Without a shader only the colors and what the texture is sampled at are needed
+        let mut buffer = vec![];
+        let layouts = objects
+            .iter()
+            .map(|o| {
+                let count = o.vertices().len();
+                let texture = o.texture();
+                let position = VertexAttribute {
+                    semantic: Semantic::Position,
+                    values: AttributeValues::Vec3(o.vertices()),
+                };
+                o.attributes(&mut buffer);
+                let attributes = buffer.drain(..).filter(|a| {
+                    a.values.len() >= count
+                        && (self.fragment_shader.is_some()
+                            || a.semantic == Semantic::Color
+                            || (texture.is_some() && a.semantic == Semantic::TexCoord(0)))
+                });
+                match self.fragment_shader {
+                    Some(_) => {
+                        VaryingLayout::new(std::iter::once(position).chain(attributes), texture)
+                    }
+                    None => VaryingLayout::new(attributes, texture),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let view_projection = camera.view_projection((width, height));
+        let mut triangles = vec![];
+        let mut lines = vec![];
+        for (object_index, o) in objects.iter().enumerate() {
+            let points = o
+                .vertices()
+                .iter()
+                .map(|v| {
+                    // Note: this is old version of the uncommented code below
+                    // this does not use matrices but reaches the same result
+                    // // Project points onto the canvas
+                    // let x_screen = (v.x / (-v.z)) * camera.near;
+                    // let y_screen = (v.y / (-v.z)) * camera.near;
+                    // println!("Screen space: {x_screen}, {y_screen}");
+                    // // Remap points into NDC (Normalized Device Coordinates) space [-1; 1].
+                    // let x_ndc = (2f32 * v.x) / canvas.width;
+                    // let y_ndc = (2f32 * v.y) / canvas.height;
+                    // println!("NDC: {x_ndc}, {y_ndc}");
+
+                    // Same transformation as `Camera::project`, so that picking
+                    // and other screen space queries agree with the image
+                    let v = Camera::project_with(&view_projection, *v, (width, height));
+                    // Keep z coordinate for z-buffering
+                    Vec3::new(v.x + rect.x0 as f32, v.y + rect.y0 as f32, v.z)
+                })
+                .collect::<Vec<_>>();
+            let distances = points
+                .iter()
+                .map(|p| camera.linear_depth(p.z))
+                .collect::<Vec<_>>();
+            let indices = o.indices();
+
+            if self.show_polygons {
+                let colors = layouts[object_index].get(Semantic::Color);
+                triangles.extend(indices.iter().enumerate().map(|(triangle_index, t)| {
+                    let points = [points[t.0], points[t.1], points[t.2]];
+                    let vertices = [t.0, t.1, t.2];
+                    let distances = vertices.map(|v| distances[v]);
+                    Triangle {
+                        object_index,
+                        triangle_index,
+                        points,
+                        distances,
+                        // Attributes are interpolated linearly in camera space,
+                        // which is not linear in raster space under perspective
+                        inv_w: distances.map(|d| match camera.projection {
+                            Projection::Perspective => 1f32 / d,
+                            Projection::Orthographic { .. } => 1f32,
+                        }),
+                        vertices,
+                        translucent: colors
+                            .is_some_and(|c| vertices.iter().any(|v| c.get(*v).w < 1f32)),
+                    }
+                }));
+            }
+
+            if self.show_wireframe && frame.is_some() {
+                lines.extend(indices.iter().map(|t| {
+                    LineBuilder::<WuLine>::new()
+                        .color(Srgba::new(0.7f32, 0.5f32, 0.6f32, 1f32))
+                        .from((points[t.0].x as i32, points[t.0].y as i32))
+                        .to((points[t.1].x as i32, points[t.1].y as i32))
+                        .to((points[t.2].x as i32, points[t.2].y as i32))
+                        .close()
+                        .shape()
+                }));
+            }
+        }
+
+        let triangles = self.sort_translucent(triangles);
+
+        let output_width = self.output_width as usize;
+        let targets = Targets {
+            output_width,
+            depth: depth_buffer,
+            ids: ids.as_deref().map(|ids| ids.entries.as_slice()),
+            color: frame.as_deref(),
+            transparency: self.transparency,
+            shader: self.fragment_shader.as_ref(),
+        };
+        let tiles = bin(&triangles, clip)
+            .into_par_iter()
+            .map(|(tile, bin)| shade_tile(&triangles, &layouts, &bin, tile, &targets))
+            .collect::<Vec<_>>();
+
+        // Tiles do not overlap, so writing them back in any order gives the same result
+        for tile in tiles {
+            let (tile_width, x0) = (tile.rect.width() as usize, tile.rect.x0 as usize);
+            for (row, y) in (tile.rect.y0..tile.rect.y1).enumerate() {
+                let start = y as usize * output_width + x0;
+                let src = row * tile_width..(row + 1) * tile_width;
+                depth_buffer[start..start + tile_width].copy_from_slice(&tile.depth[src.clone()]);
+                if let Some(ids) = ids.as_deref_mut() {
+                    ids.entries[start..start + tile_width].copy_from_slice(&tile.ids[src.clone()]);
+                }
+                if let Some(frame) = frame.as_deref_mut() {
+                    let dest = frame_row(frame, self.output_width, y, tile.rect.x0..tile.rect.x1);
+                    for (pixel, color) in frame[dest].iter_mut().zip(&tile.color[src]) {
+                        pixel.copy_from_slice(color);
+                    }
+                }
+            }
+        }
+        lines
+    }
+
+    /// Moves the translucent triangles after the opaque ones, in the order they are blended.
+    fn sort_translucent(&self, triangles: Vec<Triangle>) -> Vec<Triangle> {
+        let (mut opaque, mut translucent): (Vec<_>, Vec<_>) =
+            triangles.into_iter().partition(|t| !t.translucent);
+        let center = |t: &Triangle| t.distances.iter().sum::<f32>() / 3f32;
+
+        // Sorts are stable, so equally distant triangles keep their order
+        match self.transparency {
+            Transparency::SortObjects => {
+                let mut sums: Vec<(f32, usize)> = vec![];
+                for t in &translucent {
+                    if sums.len() <= t.object_index {
+                        sums.resize(t.object_index + 1, (0f32, 0));
+                    }
+                    sums[t.object_index].0 += center(t);
+                    sums[t.object_index].1 += 1;
+                }
+                let distance = |t: &Triangle| {
+                    let (sum, count) = sums[t.object_index];
+                    sum / count as f32
+                };
+                translucent.sort_by(|a, b| distance(b).total_cmp(&distance(a)));
+            }
+            Transparency::SortTriangles => {
+                translucent.sort_by(|a, b| center(b).total_cmp(&center(a)));
+            }
+            Transparency::WeightedBlended => {}
+        }
+
+        opaque.append(&mut translucent);
+        opaque
+    }
+}
+
+/// Side in pixels of the square tiles shaded in parallel
+const TILE_SIZE: i32 = 64;
+
+/// A triangle projected into raster space, ready to be shaded.
+struct Triangle {
+    object_index: usize,
+    triangle_index: usize,
+    /// Raster position and depth of every vertex
+    points: [Vec3; 3],
+    /// Distance in front of the camera of every vertex
+    distances: [f32; 3],
+    /// Reciprocal of the distance in front of the camera of every vertex
+    inv_w: [f32; 3],
+    /// Indices of the vertices in the mesh, to interpolate their attributes
+    vertices: [usize; 3],
+    /// Blended over what is behind it without writing depth
+    translucent: bool,
+}
+
+/// Buffers the tiles are shaded into, all `output_width` pixels wide, and how.
+/// Ids and colors are only written if there is a buffer for them.
+struct Targets<'a> {
+    output_width: usize,
+    depth: &'a [f32],
+    ids: Option<&'a [Option<Pick>]>,
+    color: Option<&'a [&'a mut [u8]]>,
+    transparency: Transparency,
+    shader: Option<&'a FragmentShader>,
+}
+
+/// What shading a tile leaves behind.
+struct ShadedTile {
+    rect: Rect,
+    /// Depth of every pixel of the tile row by row
+    depth: Vec<f32>,
+    /// Closest triangle at every pixel of the tile, empty if ids are not recorded
+    ids: Vec<Option<Pick>>,
+    /// Color of every pixel of the tile, empty if colors are not written
+    color: Vec<[u8; 4]>,
+}
+
+/// Splits `clip` into tiles and lists the triangles whose bounding box touches each of them,
+/// in the order the triangles were submitted. Tiles without triangles are left out.
+fn bin(triangles: &[Triangle], clip: &Rect) -> Vec<(Rect, Vec<usize>)> {
+    let columns = (clip.width() + TILE_SIZE - 1) / TILE_SIZE;
+    let rows = (clip.height() + TILE_SIZE - 1) / TILE_SIZE;
+    let mut bins = vec![vec![]; (columns * rows) as usize];
+
+    for (i, t) in triangles.iter().enumerate() {
+        if !t.points.iter().all(|p| p.is_finite()) {
+            continue;
+        }
+        let min = t.points[0].min(t.points[1]).min(t.points[2]);
+        let max = t.points[0].max(t.points[1]).max(t.points[2]);
+        // Tile coordinates of the bounding box, clamped before the conversion to stay in range
+        let tile = |v: f32, start: i32, count: i32| {
+            ((v.floor() - start as f32) / TILE_SIZE as f32)
+                .floor()
+                .clamp(-1f32, count as f32) as i32
         };
-        d.render(camera, &shapes, frame);
+        let (x0, x1) = (
+            tile(min.x, clip.x0, columns).max(0),
+            tile(max.x, clip.x0, columns).min(columns - 1),
+        );
+        let (y0, y1) = (
+            tile(min.y, clip.y0, rows).max(0),
+            tile(max.y, clip.y0, rows).min(rows - 1),
+        );
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                bins[(y * columns + x) as usize].push(i);
+            }
+        }
+    }
+
+    bins.into_iter()
+        .enumerate()
+        .filter(|(_, bin)| !bin.is_empty())
+        .map(|(i, bin)| {
+            let (x, y) = (i as i32 % columns, i as i32 / columns);
+            let x0 = clip.x0 + x * TILE_SIZE;
+            let y0 = clip.y0 + y * TILE_SIZE;
+            let rect = Rect::new(
+                x0,
+                y0,
+                (x0 + TILE_SIZE).min(clip.x1),
+                (y0 + TILE_SIZE).min(clip.y1),
+            );
+            (rect, bin)
+        })
+        .collect()
+}
+
+/// Depth tests and shades the triangles of `bin` inside of `tile`,
+/// starting from what is already in `targets`. `layouts` has the attributes of every object.
+fn shade_tile(
+    triangles: &[Triangle],
+    layouts: &[VaryingLayout],
+    bin: &[usize],
+    tile: Rect,
+    targets: &Targets,
+) -> ShadedTile {
+    let (output_width, tile_width) = (targets.output_width, tile.width() as usize);
+    let rows = || {
+        (tile.y0..tile.y1).map(move |y| {
+            let start = y as usize * output_width + tile.x0 as usize;
+            start..start + tile_width
+        })
+    };
+    let mut depth: Vec<f32> = rows().flat_map(|r| &targets.depth[r]).copied().collect();
+    let record_ids = targets.ids.is_some();
+    let mut ids: Vec<Option<Pick>> = targets
+        .ids
+        .map(|ids| rows().flat_map(|r| &ids[r]).copied().collect())
+        .unwrap_or_default();
+    // Pixels missing from the frame start out transparent
+    let mut color: Vec<[u8; 4]> = targets
+        .color
+        .map(|frame| {
+            (tile.y0..tile.y1)
+                .flat_map(|y| {
+                    let row = frame_row(frame, output_width as u32, y, tile.x0..tile.x1);
+                    frame[row]
+                        .iter()
+                        .map(|p| [p[0], p[1], p[2], p[3]])
+                        .chain(iter::repeat([0; 4]))
+                        .take(tile_width)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let shaded = targets.color.is_some();
+    let weighted_blended = targets.transparency == Transparency::WeightedBlended;
+    let mut accumulators = if shaded && weighted_blended {
+        vec![Accumulator::default(); color.len()]
+    } else {
+        vec![]
+    };
+    let mut values = vec![0f32; layouts.iter().map(|l| l.size).max().unwrap_or(0)];
+
+    for t in bin.iter().map(|i| &triangles[*i]) {
+        // Translucent triangles hide nothing, without colors there is nothing left to do
+        if t.translucent && !shaded {
+            break;
+        }
+        let (p, inv_w) = (t.points, t.inv_w);
+        let layout = &layouts[t.object_index];
+        // Without a shader or a texture only the colors of the vertices are needed
+        let colors = match layout.get(Semantic::Color) {
+            Some(c) if targets.shader.is_none() => t.vertices.map(|v| c.get(v)),
+            _ => [Vec4::ONE; 3],
+        };
+        let interpolate = targets.shader.is_some() || layout.texture.is_some();
+        raster::triangle(p.map(|p| p.truncate()), &tile, |x, y, w| {
+            // Depth after projection is linear in raster space
+            let z = w[0] * p[0].z + w[1] * p[1].z + w[2] * p[2].z;
+            let idx = (y - tile.y0) as usize * tile_width + (x - tile.x0) as usize;
+            if z >= depth[idx] {
+                return;
+            }
+            if !t.translucent {
+                depth[idx] = z;
+            }
+            let pw = [w[0] * inv_w[0], w[1] * inv_w[1], w[2] * inv_w[2]];
+            let sum = pw[0] + pw[1] + pw[2];
+            let weights = pw.map(|w| w / sum);
+            if record_ids && !t.translucent {
+                ids[idx] = Some(Pick {
+                    object_index: t.object_index,
+                    triangle_index: t.triangle_index,
+                    barycentrics: Vec3::from(weights),
+                    depth: z,
+                });
+            }
+
+            if !shaded {
+                return;
+            }
+            let d = t.distances;
+            let distance = weights[0] * d[0] + weights[1] * d[1] + weights[2] * d[2];
+            let fragment = if interpolate {
+                let values = &mut values[..layout.size];
+                layout.interpolate(t.vertices, weights, values);
+                let fragment = Fragment {
+                    x,
+                    y,
+                    distance,
+                    object_index: t.object_index,
+                    triangle_index: t.triangle_index,
+                    layout,
+                    values,
+                };
+                match targets.shader {
+                    Some(shader) => shader(&fragment),
+                    None => fragment
+                        .color()
+                        .unwrap_or(Srgba::new(1f32, 1f32, 1f32, 1f32)),
+                }
+            } else {
+                let c = colors[0] * weights[0] + colors[1] * weights[1] + colors[2] * weights[2];
+                Srgba::new(c.x, c.y, c.z, c.w)
+            };
+            if t.translucent && weighted_blended {
+                accumulators[idx].add(fragment, distance);
+            } else {
+                write_fragment(&mut color[idx], fragment);
+            }
+        });
+    }
+
+    for (pixel, accumulator) in color.iter_mut().zip(&accumulators) {
+        accumulator.resolve(pixel);
+    }
+
+    ShadedTile {
+        rect: tile,
+        depth,
+        ids,
+        color,
+    }
+}
+
+/// Writes `color` into `pixel`, blending it over what is there unless it is opaque.
+fn write_fragment(pixel: &mut [u8; 4], color: Srgba) {
+    let color = if color.alpha >= 1f32 {
+        color
+    } else {
+        let dest: Srgba<f32> = Srgba::new(pixel[0], pixel[1], pixel[2], pixel[3]).into_format();
+        color.over(dest)
+    };
+    *pixel = color.into_format().into();
+}
+
+impl Renderer for Rasterizer {
+    type Renderable = Box<dyn Mesh3D>;
+
+    fn render(&self, camera: &Camera, objects: &[Self::Renderable], frame: &mut [&mut [u8]]) {
+        self.render_viewports(&[self.full_viewport(camera)], objects, frame);
     }
 
     fn set_output_dimensions(&mut self, width: u32, height: u32) {
@@ -187,8 +719,3 @@ impl Renderer for Rasterizer {
         self.output_height = height;
     }
 }
-
-fn edge_function(a: (f32, f32), b: (f32, f32), p: (f32, f32)) -> f32 {
-    // TODO: there should be no `-` sign
-    -((p.0 - a.0) * (b.1 - a.1) - (p.1 - a.1) * (b.0 - a.0))
-}
@@ -0,0 +1,315 @@
+use crate::drawing::{Rect, Shape2D};
+
+/// Stable reference to a shape stored in a `Scene2D`.
+/// Removing a shape invalidates its handle, operations on stale handles are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShapeHandle {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct SceneNode {
+    pub shape: Shape2D,
+    pub name: Option<String>,
+    pub group: Option<String>,
+    pub visible: bool,
+    /// Shapes with higher `z` are drawn on top
+    pub z: i32,
+    bounds: Option<Rect>,
+    sequence: u64,
+}
+
+impl SceneNode {
+    /// Bounding box of the shape as of the last change
+    pub fn bounds(&self) -> Option<Rect> {
+        self.bounds
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Slot {
+    generation: u32,
+    node: Option<SceneNode>,
+}
+
+/// Retained set of 2D shapes that can be modified in place.
+///
+/// The scene remembers the areas touched by every modification,
+/// which lets a renderer repaint only what changed.
+#[derive(Debug, Clone)]
+pub struct Scene2D {
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+    /// Indices of occupied slots in drawing order
+    order: Vec<u32>,
+    next_sequence: u64,
+    damage: Vec<Rect>,
+    invalidated: bool,
+    /// Frame the shapes were drawn over, restored under them with `Background::Keep`
+    pub(crate) kept: Vec<u8>,
+}
+
+impl Default for Scene2D {
+    fn default() -> Self {
+        Self {
+            slots: vec![],
+            free: vec![],
+            order: vec![],
+            next_sequence: 0,
+            damage: vec![],
+            // Nothing has been drawn yet
+            invalidated: true,
+            kept: vec![],
+        }
+    }
+}
+
+impl Scene2D {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a visible shape on top of every shape with the same `z`.
+    pub fn add(&mut self, shape: Shape2D) -> ShapeHandle {
+        self.add_node(shape, None, None)
+    }
+
+    /// Adds a visible shape with a name and an optional group.
+    pub fn add_named(
+        &mut self,
+        name: impl Into<String>,
+        group: Option<&str>,
+        shape: Shape2D,
+    ) -> ShapeHandle {
+        self.add_node(shape, Some(name.into()), group.map(String::from))
+    }
+
+    fn add_node(
+        &mut self,
+        shape: Shape2D,
+        name: Option<String>,
+        group: Option<String>,
+    ) -> ShapeHandle {
+        let bounds = shape.bounds();
+        self.damage.extend(bounds);
+
+        let node = SceneNode {
+            shape,
+            name,
+            group,
+            visible: true,
+            z: 0,
+            bounds,
+            sequence: self.next_sequence,
+        };
+        self.next_sequence += 1;
+
+        let index = match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.node = Some(node);
+                index
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    node: Some(node),
+                });
+                self.slots.len() as u32 - 1
+            }
+        };
+        self.insert_ordered(index);
+
+        ShapeHandle {
+            index,
+            generation: self.slots[index as usize].generation,
+        }
+    }
+
+    /// Removes the shape and returns it, `None` if the handle is stale.
+    pub fn remove(&mut self, handle: ShapeHandle) -> Option<Shape2D> {
+        self.get(handle)?;
+        let slot = &mut self.slots[handle.index as usize];
+        let node = slot.node.take()?;
+        slot.generation += 1;
+        self.free.push(handle.index);
+        self.order.retain(|i| *i != handle.index);
+        if node.visible {
+            self.damage.extend(node.bounds);
+        }
+        Some(node.shape)
+    }
+
+    /// Replaces the shape behind `handle` and returns the old one.
+    pub fn update(&mut self, handle: ShapeHandle, shape: Shape2D) -> Option<Shape2D> {
+        let bounds = shape.bounds();
+        let node = self.node_mut(handle)?;
+        let old_bounds = std::mem::replace(&mut node.bounds, bounds);
+        let old = std::mem::replace(&mut node.shape, shape);
+        if node.visible {
+            let changed = match (old_bounds, bounds) {
+                (Some(a), Some(b)) => Some(a.union(&b)),
+                (a, b) => a.or(b),
+            };
+            self.damage.extend(changed);
+        }
+        Some(old)
+    }
+
+    pub fn set_visible(&mut self, handle: ShapeHandle, visible: bool) {
+        let Some(node) = self.node_mut(handle) else {
+            return;
+        };
+        if node.visible != visible {
+            node.visible = visible;
+            let bounds = node.bounds;
+            self.damage.extend(bounds);
+        }
+    }
+
+    pub fn set_name(&mut self, handle: ShapeHandle, name: Option<&str>) {
+        if let Some(node) = self.node_mut(handle) {
+            node.name = name.map(String::from);
+        }
+    }
+
+    pub fn set_group(&mut self, handle: ShapeHandle, group: Option<&str>) {
+        if let Some(node) = self.node_mut(handle) {
+            node.group = group.map(String::from);
+        }
+    }
+
+    /// Moves the shape to layer `z`, on top of the shapes already there.
+    pub fn set_z(&mut self, handle: ShapeHandle, z: i32) {
+        let sequence = self.next_sequence;
+        let Some(node) = self.node_mut(handle) else {
+            return;
+        };
+        node.z = z;
+        node.sequence = sequence;
+        let (visible, bounds) = (node.visible, node.bounds);
+        self.next_sequence += 1;
+
+        self.order.retain(|i| *i != handle.index);
+        self.insert_ordered(handle.index);
+        if visible {
+            self.damage.extend(bounds);
+        }
+    }
+
+    /// Draws the shape above every other shape.
+    pub fn bring_to_front(&mut self, handle: ShapeHandle) {
+        let top = self.nodes().map(|(_, n)| n.z).max().unwrap_or(0);
+        self.set_z(handle, top);
+    }
+
+    /// Draws the shape below every other shape.
+    pub fn send_to_back(&mut self, handle: ShapeHandle) {
+        let Some(bottom) = self.nodes().map(|(_, n)| n.z).min() else {
+            return;
+        };
+        // Shapes inserted into a layer go on top of it, so go one layer lower
+        let z = if self.nodes().any(|(h, n)| n.z == bottom && h != handle) {
+            bottom - 1
+        } else {
+            bottom
+        };
+        self.set_z(handle, z);
+    }
+
+    pub fn get(&self, handle: ShapeHandle) -> Option<&SceneNode> {
+        let slot = self.slots.get(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        slot.node.as_ref()
+    }
+
+    fn node_mut(&mut self, handle: ShapeHandle) -> Option<&mut SceneNode> {
+        let slot = self.slots.get_mut(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        slot.node.as_mut()
+    }
+
+    /// Finds the first shape, in drawing order, called `name`.
+    pub fn find(&self, name: &str) -> Option<ShapeHandle> {
+        self.nodes()
+            .find(|(_, n)| n.name.as_deref() == Some(name))
+            .map(|(h, _)| h)
+    }
+
+    /// Handles of every shape in `group`, in drawing order.
+    pub fn group<'a>(&'a self, group: &'a str) -> impl Iterator<Item = ShapeHandle> + 'a {
+        self.nodes()
+            .filter(move |(_, n)| n.group.as_deref() == Some(group))
+            .map(|(h, _)| h)
+    }
+
+    pub fn set_group_visible(&mut self, group: &str, visible: bool) {
+        for handle in self.group(group).collect::<Vec<_>>() {
+            self.set_visible(handle, visible);
+        }
+    }
+
+    /// Every shape with its handle, in drawing order.
+    pub fn nodes(&self) -> impl Iterator<Item = (ShapeHandle, &SceneNode)> {
+        self.order.iter().map(|i| {
+            let slot = &self.slots[*i as usize];
+            let handle = ShapeHandle {
+                index: *i,
+                generation: slot.generation,
+            };
+            (handle, slot.node.as_ref().unwrap())
+        })
+    }
+
+    /// Visible shapes in drawing order.
+    pub fn shapes(&self) -> impl Iterator<Item = &Shape2D> {
+        self.nodes()
+            .filter(|(_, n)| n.visible)
+            .map(|(_, n)| &n.shape)
+    }
+
+    /// Visible shapes overlapping `rect`, in drawing order.
+    pub fn shapes_in<'a>(&'a self, rect: &'a Rect) -> impl Iterator<Item = &'a Shape2D> + 'a {
+        self.nodes()
+            .filter(|(_, n)| n.visible && n.bounds.is_some_and(|b| b.intersection(rect).is_some()))
+            .map(|(_, n)| &n.shape)
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Areas that changed since the last call.
+    /// Each one covers the old and the new bounds of a modified shape.
+    pub fn take_damage(&mut self) -> Vec<Rect> {
+        std::mem::take(&mut self.damage)
+    }
+
+    /// Marks the whole scene as changed, e.g. after the output was resized.
+    pub fn invalidate(&mut self) {
+        self.invalidated = true;
+    }
+
+    pub(crate) fn take_invalidated(&mut self) -> bool {
+        std::mem::take(&mut self.invalidated)
+    }
+
+    fn insert_ordered(&mut self, index: u32) {
+        let key = |i: u32| {
+            let n = self.slots[i as usize].node.as_ref().unwrap();
+            (n.z, n.sequence)
+        };
+        let k = key(index);
+        let position = self.order.partition_point(|i| key(*i) < k);
+        self.order.insert(position, index);
+    }
+}
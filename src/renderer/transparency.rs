@@ -0,0 +1,71 @@
+use palette::Srgba;
+
+/// How the rasterizer orders translucent triangles.
+///
+/// Opaque triangles are always drawn first. Translucent ones are tested against their depth
+/// but never write depth, so they do not hide anything drawn after them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Transparency {
+    /// Draws meshes back to front by the mean distance of their translucent triangles,
+    /// triangles of one mesh keep their order
+    SortObjects,
+    /// Draws triangles back to front by the distance of their centers
+    #[default]
+    SortTriangles,
+    /// Weighted blended order-independent transparency.
+    /// Needs no sorting and has no popping, but only approximates the real blend.
+    WeightedBlended,
+}
+
+/// Translucent fragments gathered at a pixel for `Transparency::WeightedBlended`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Accumulator {
+    /// Weighted sum of premultiplied colors, the weighted sum of alphas is the last component
+    color: [f32; 4],
+    /// Fraction of the background that shows through all of the fragments
+    revealage: f32,
+}
+
+impl Default for Accumulator {
+    fn default() -> Self {
+        Self {
+            color: [0f32; 4],
+            revealage: 1f32,
+        }
+    }
+}
+
+impl Accumulator {
+    /// Adds a fragment `distance` in front of the camera.
+    pub(crate) fn add(&mut self, color: Srgba, distance: f32) {
+        // Closer fragments get a larger weight, eq. 10 from
+        // McGuire and Bavoil, Weighted Blended Order-Independent Transparency
+        let weight = color.alpha
+            * (10f32 / (1e-5f32 + (distance / 5f32).powi(2) + (distance / 200f32).powi(6)))
+                .clamp(1e-2f32, 3e3f32);
+        let premultiplied = [color.red, color.green, color.blue, 1f32].map(|c| c * color.alpha);
+        for (sum, c) in self.color.iter_mut().zip(premultiplied) {
+            *sum += c * weight;
+        }
+        self.revealage *= 1f32 - color.alpha;
+    }
+
+    /// Composites the gathered fragments over `pixel`.
+    pub(crate) fn resolve(&self, pixel: &mut [u8; 4]) {
+        if self.revealage >= 1f32 {
+            return;
+        }
+        let coverage = 1f32 - self.revealage;
+        let weight = self.color[3].max(1e-5f32);
+        let dest: Srgba<f32> = Srgba::new(pixel[0], pixel[1], pixel[2], pixel[3]).into_format();
+        let dest = [dest.red, dest.green, dest.blue, dest.alpha];
+        let mut out = [0f32; 4];
+        for c in 0..3 {
+            out[c] = self.color[c] / weight * coverage + dest[c] * self.revealage;
+        }
+        out[3] = coverage + dest[3] * self.revealage;
+        *pixel = Srgba::new(out[0], out[1], out[2], out[3])
+            .into_format()
+            .into();
+    }
+}
@@ -0,0 +1,219 @@
+use std::{borrow::Cow, sync::Arc};
+
+use glam::{Vec2, Vec3, Vec4};
+use palette::Srgba;
+
+use crate::formats::Texture;
+
+/// What the values of a vertex attribute mean.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Semantic {
+    /// World space position, always taken from `Mesh3D::vertices`
+    Position,
+    Normal,
+    /// Direction of increasing u in xyz and the handedness of the bitangent in w
+    Tangent,
+    /// Texture coordinates of the given set, 0 for the first one
+    TexCoord(u8),
+    /// Triangles with a vertex that is not fully opaque are blended over what is behind them
+    Color,
+    JointWeights,
+    /// Anything else, told apart by name, which may come from a file
+    Custom(Cow<'static, str>),
+}
+
+/// Values of one attribute for every vertex of a mesh, borrowed from the mesh.
+#[derive(Debug, Clone, Copy)]
+pub enum AttributeValues<'a> {
+    Float(&'a [f32]),
+    Vec2(&'a [Vec2]),
+    Vec3(&'a [Vec3]),
+    Vec4(&'a [Vec4]),
+    Color(&'a [Srgba]),
+}
+
+impl AttributeValues<'_> {
+    pub fn len(&self) -> usize {
+        match self {
+            AttributeValues::Float(v) => v.len(),
+            AttributeValues::Vec2(v) => v.len(),
+            AttributeValues::Vec3(v) => v.len(),
+            AttributeValues::Vec4(v) => v.len(),
+            AttributeValues::Color(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of floats in every value
+    pub fn components(&self) -> usize {
+        match self {
+            AttributeValues::Float(_) => 1,
+            AttributeValues::Vec2(_) => 2,
+            AttributeValues::Vec3(_) => 3,
+            AttributeValues::Vec4(_) | AttributeValues::Color(_) => 4,
+        }
+    }
+
+    /// Value of vertex `index`, padded with zeros.
+    pub fn get(&self, index: usize) -> Vec4 {
+        match self {
+            AttributeValues::Float(v) => Vec4::new(v[index], 0f32, 0f32, 0f32),
+            AttributeValues::Vec2(v) => v[index].extend(0f32).extend(0f32),
+            AttributeValues::Vec3(v) => v[index].extend(0f32),
+            AttributeValues::Vec4(v) => v[index],
+            AttributeValues::Color(v) => {
+                let c = v[index];
+                Vec4::new(c.red, c.green, c.blue, c.alpha)
+            }
+        }
+    }
+}
+
+/// Owned values of one attribute for every vertex of a mesh.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeBuffer {
+    Float(Vec<f32>),
+    Vec2(Vec<Vec2>),
+    Vec3(Vec<Vec3>),
+    Vec4(Vec<Vec4>),
+    Color(Vec<Srgba>),
+}
+
+impl AttributeBuffer {
+    pub fn values(&self) -> AttributeValues<'_> {
+        match self {
+            AttributeBuffer::Float(v) => AttributeValues::Float(v),
+            AttributeBuffer::Vec2(v) => AttributeValues::Vec2(v),
+            AttributeBuffer::Vec3(v) => AttributeValues::Vec3(v),
+            AttributeBuffer::Vec4(v) => AttributeValues::Vec4(v),
+            AttributeBuffer::Color(v) => AttributeValues::Color(v),
+        }
+    }
+}
+
+/// Per-vertex data of a mesh besides its positions.
+#[derive(Debug, Clone)]
+pub struct VertexAttribute<'a> {
+    pub semantic: Semantic,
+    /// One value for every vertex in `Mesh3D::vertices`
+    pub values: AttributeValues<'a>,
+}
+
+/// Computes the color of a pixel covered by a triangle from its interpolated attributes.
+pub type FragmentShader = Arc<dyn Fn(&Fragment) -> Srgba + Send + Sync>;
+
+/// A pixel covered by a triangle, with the attributes of the triangle vertices interpolated
+/// with perspective correction.
+#[derive(Debug)]
+pub struct Fragment<'a> {
+    pub x: i32,
+    pub y: i32,
+    /// Distance in front of the camera
+    pub distance: f32,
+    /// Index of the mesh in the rendered objects
+    pub object_index: usize,
+    /// Index of the triangle in `Mesh3D::indices`
+    pub triangle_index: usize,
+    pub(crate) layout: &'a VaryingLayout<'a>,
+    pub(crate) values: &'a [f32],
+}
+
+impl Fragment<'_> {
+    /// Interpolated value of the attribute, `None` if the mesh does not have it.
+    pub fn attribute(&self, semantic: Semantic) -> Option<&[f32]> {
+        self.layout
+            .attributes
+            .iter()
+            .find(|(a, _)| a.semantic == semantic)
+            .map(|(a, offset)| &self.values[*offset..*offset + a.values.components()])
+    }
+
+    pub fn position(&self) -> Option<Vec3> {
+        self.attribute(Semantic::Position).map(Vec3::from_slice)
+    }
+
+    /// Interpolated normal, normalized again
+    pub fn normal(&self) -> Option<Vec3> {
+        self.attribute(Semantic::Normal)
+            .map(|n| Vec3::from_slice(n).normalize_or_zero())
+    }
+
+    pub fn tex_coord(&self, set: u8) -> Option<Vec2> {
+        self.attribute(Semantic::TexCoord(set))
+            .map(Vec2::from_slice)
+    }
+
+    /// Interpolated color multiplied with the texture of the mesh at `tex_coord(0)`,
+    /// `None` if the mesh has neither colors nor a texture
+    pub fn color(&self) -> Option<Srgba> {
+        let color = self
+            .attribute(Semantic::Color)
+            .map(|c| Srgba::new(c[0], c[1], c[2], c[3]));
+        let (Some(texture), Some(uv)) = (self.layout.texture, self.tex_coord(0)) else {
+            return color;
+        };
+        let c = color.unwrap_or(Srgba::new(1f32, 1f32, 1f32, 1f32));
+        let t = texture.sample(uv);
+        Some(Srgba::new(
+            c.red * t.red,
+            c.green * t.green,
+            c.blue * t.blue,
+            c.alpha * t.alpha,
+        ))
+    }
+}
+
+/// Attributes of one mesh that are interpolated, packed one after another.
+#[derive(Debug)]
+pub(crate) struct VaryingLayout<'a> {
+    /// Attributes with the offset of their first component
+    attributes: Vec<(VertexAttribute<'a>, usize)>,
+    /// Number of floats of all attributes
+    pub(crate) size: usize,
+    pub(crate) texture: Option<&'a Texture>,
+}
+
+impl<'a> VaryingLayout<'a> {
+    pub(crate) fn new(
+        attributes: impl IntoIterator<Item = VertexAttribute<'a>>,
+        texture: Option<&'a Texture>,
+    ) -> Self {
+        let mut size = 0;
+        let attributes = attributes
+            .into_iter()
+            .map(|a| {
+                let offset = size;
+                size += a.values.components();
+                (a, offset)
+            })
+            .collect();
+        Self {
+            attributes,
+            size,
+            texture,
+        }
+    }
+
+    pub(crate) fn get(&self, semantic: Semantic) -> Option<AttributeValues<'a>> {
+        self.attributes
+            .iter()
+            .find(|(a, _)| a.semantic == semantic)
+            .map(|(a, _)| a.values)
+    }
+
+    /// Writes the sum of the attributes of `vertices` scaled by `weights` into `values`.
+    /// Weights that are perspective correct give perspective correct values.
+    pub(crate) fn interpolate(&self, vertices: [usize; 3], weights: [f32; 3], values: &mut [f32]) {
+        for (attribute, offset) in &self.attributes {
+            let v = &attribute.values;
+            let value = v.get(vertices[0]) * weights[0]
+                + v.get(vertices[1]) * weights[1]
+                + v.get(vertices[2]) * weights[2];
+            let components = v.components();
+            values[*offset..offset + components].copy_from_slice(&value.to_array()[..components]);
+        }
+    }
+}
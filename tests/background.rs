@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use palette::Srgba;
+use pixel_renderer::{
+    camera::Camera,
+    formats::Texture,
+    renderer::{Background, Drawifier, Renderer},
+};
+
+const WIDTH: u32 = 8;
+const HEIGHT: u32 = 6;
+
+/// Frame with nothing but `background`, drawn over a pattern
+fn render(background: Background) -> Vec<[u8; 4]> {
+    let drawifier = Drawifier {
+        output_width: WIDTH,
+        output_height: HEIGHT,
+        background,
+    };
+    let mut buffer = pattern();
+    let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+    drawifier.render(&Camera::default(), &[], &mut frame);
+    buffer
+        .chunks_exact(4)
+        .map(|p| p.try_into().unwrap())
+        .collect()
+}
+
+fn pattern() -> Vec<u8> {
+    (0..WIDTH * HEIGHT * 4)
+        .map(|i| (i * 7 % 251) as u8)
+        .collect()
+}
+
+fn pixel(frame: &[[u8; 4]], x: u32, y: u32) -> [u8; 4] {
+    frame[(y * WIDTH + x) as usize]
+}
+
+#[test]
+fn gradients_reach_their_colors_at_the_edges() {
+    let (red, blue) = (
+        Srgba::new(1f32, 0f32, 0f32, 1f32),
+        Srgba::new(0f32, 0f32, 1f32, 1f32),
+    );
+    let frame = render(Background::VerticalGradient {
+        top: red,
+        bottom: blue,
+    });
+    for x in 0..WIDTH {
+        assert_eq!(pixel(&frame, x, 0), [255, 0, 0, 255]);
+        assert_eq!(pixel(&frame, x, HEIGHT - 1), [0, 0, 255, 255]);
+    }
+    let middle = pixel(&frame, 0, HEIGHT / 2);
+    assert!(middle[0] > 0 && middle[2] > 0, "{middle:?}");
+
+    let frame = render(Background::HorizontalGradient {
+        left: red,
+        right: blue,
+    });
+    for y in 0..HEIGHT {
+        assert_eq!(pixel(&frame, 0, y), [255, 0, 0, 255]);
+        assert_eq!(pixel(&frame, WIDTH - 1, y), [0, 0, 255, 255]);
+    }
+}
+
+#[test]
+fn keep_and_transparent_backgrounds() {
+    let kept: Vec<u8> = render(Background::Keep).concat();
+    assert_eq!(kept, pattern());
+    assert!(render(Background::Transparent)
+        .iter()
+        .all(|p| *p == [0, 0, 0, 0]));
+}
+
+#[test]
+fn images_are_stretched_over_the_output() {
+    // Red and green on top, blue and white below
+    let data = [
+        [255, 0, 0, 255],
+        [0, 255, 0, 255],
+        [0, 0, 255, 255],
+        [255; 4],
+    ];
+    let image = Texture::new(2, 2, data.concat()).unwrap();
+    let frame = render(Background::Image(Arc::new(image)));
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let texel = (y / (HEIGHT / 2)) * 2 + x / (WIDTH / 2);
+            assert_eq!(pixel(&frame, x, y), data[texel as usize], "({x}, {y})");
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::f32::consts::FRAC_PI_2;
+
+use glam::{EulerRot, Quat, Vec3};
+use pixel_renderer::camera::Camera;
+use radians::Rad32;
+
+fn cameras() -> Vec<Camera> {
+    [
+        (0f32, 0f32, 0f32),
+        (1.2f32, -0.4f32, 0f32),
+        (-2.5f32, 1f32, 0.3f32),
+    ]
+    .into_iter()
+    .map(|(yaw, pitch, roll)| Camera {
+        position: Vec3::new(yaw, pitch, roll) * 3f32,
+        yaw: Rad32::new(yaw),
+        pitch: Rad32::new(pitch),
+        roll: Rad32::new(roll),
+        ..Default::default()
+    })
+    .collect()
+}
+
+#[test]
+fn axes_match_world_to_camera() {
+    for camera in cameras() {
+        let view = camera.world_to_camera();
+        assert!(view
+            .transform_point3(camera.position)
+            .abs_diff_eq(Vec3::ZERO, 1e-5f32));
+        for (axis, expected) in [
+            (camera.forward(), Vec3::NEG_Z),
+            (camera.right(), Vec3::X),
+            (camera.up(), Vec3::Y),
+        ] {
+            let axis = view.transform_vector3(axis);
+            assert!(axis.abs_diff_eq(expected, 1e-5f32), "{axis} for {expected}");
+        }
+    }
+}
+
+#[test]
+fn orientation_round_trips() {
+    for camera in cameras() {
+        let mut copy = Camera::default();
+        copy.set_orientation(camera.orientation());
+        assert!((copy.pitch.val() - camera.pitch.val()).abs() < 1e-5f32);
+        assert!((copy.roll.val() - camera.roll.val()).abs() < 1e-5f32);
+        assert!(copy
+            .orientation()
+            .abs_diff_eq(camera.orientation(), 1e-5f32));
+    }
+
+    // Rotations built any other way come back as the same rotation
+    let rotation = Quat::from_euler(EulerRot::XYZ, 0.3f32, -1.1f32, 0.2f32);
+    let mut camera = Camera::default();
+    camera.set_orientation(rotation);
+    let v = Vec3::new(1f32, 2f32, 3f32);
+    assert!((camera.orientation() * v).abs_diff_eq(rotation * v, 1e-5f32));
+}
+
+#[test]
+fn looks_at_the_target() {
+    let mut camera = Camera {
+        position: Vec3::new(1f32, 2f32, 3f32),
+        ..Default::default()
+    };
+    let target = Vec3::new(-2f32, 0f32, -1f32);
+    camera.look_at(target, Vec3::Y);
+    let forward = (target - camera.position).normalize();
+    assert!(camera.forward().abs_diff_eq(forward, 1e-5f32));
+    assert!(camera.right().y.abs() < 1e-5f32);
+    assert!(camera.up().y > 0f32);
+    assert!(camera.roll.val().abs() < 1e-5f32);
+
+    // The target ends up in the middle of the image
+    let center = camera.project(target, (64, 48));
+    assert!(center
+        .truncate()
+        .abs_diff_eq((32f32, 24f32).into(), 1e-3f32));
+
+    // Looking straight down keeps the top of the camera where it was
+    let up = camera.up();
+    camera.look_at(camera.position - Vec3::Y, Vec3::Y);
+    assert!(camera.forward().abs_diff_eq(Vec3::NEG_Y, 1e-3f32));
+    assert!(camera.up().dot(up) > 0f32);
+
+    // Looking at its own position leaves the camera as it was
+    let before = camera.orientation();
+    camera.look_at(camera.position, Vec3::Y);
+    assert_eq!(camera.orientation(), before);
+}
+
+#[test]
+fn looks_straight_up_and_down_from_a_level_camera() {
+    for direction in [Vec3::Y, Vec3::NEG_Y] {
+        let mut camera = Camera {
+            position: Vec3::new(1f32, 2f32, 3f32),
+            ..Default::default()
+        };
+        let (forward, right) = (camera.forward(), camera.right());
+        camera.look_at(camera.position + direction, Vec3::Y);
+        assert!(camera.forward().abs_diff_eq(direction, 1e-3f32));
+        assert!((camera.pitch.val().abs() - FRAC_PI_2).abs() < 1e-3f32);
+        // The camera tilted about its right side, like a head nodding
+        assert!(camera.right().abs_diff_eq(right, 1e-3f32));
+        assert!(camera.up().abs_diff_eq(-forward * direction.y, 1e-3f32));
+    }
+}
@@ -0,0 +1,76 @@
+use pixel_renderer::{
+    drawing::Rect,
+    renderer::{BlendMode, CompositeLayer, Compositor, Layer},
+};
+
+const WIDTH: u32 = 8;
+const HEIGHT: u32 = 4;
+
+/// Fills whatever it is given with one color
+struct Fill([u8; 4]);
+
+impl Layer for Fill {
+    fn render(&self, frame: &mut [&mut [u8]]) {
+        frame.iter_mut().for_each(|p| p.copy_from_slice(&self.0));
+    }
+
+    fn set_output_dimensions(&mut self, _width: u32, _height: u32) {}
+}
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+fn render(compositor: &mut Compositor) -> Vec<[u8; 4]> {
+    let mut buffer = vec![0u8; (WIDTH * HEIGHT * 4) as usize];
+    let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+    compositor.render(&mut frame);
+    buffer
+        .chunks_exact(4)
+        .map(|p| p.try_into().unwrap())
+        .collect()
+}
+
+#[test]
+fn later_layers_are_drawn_on_top() {
+    let mut compositor = Compositor::new(WIDTH, HEIGHT);
+    compositor.push(CompositeLayer::new(Fill(RED)));
+    compositor.push(CompositeLayer::new(Fill(BLUE)));
+    assert!(render(&mut compositor).iter().all(|p| *p == BLUE));
+
+    compositor.layers.swap(0, 1);
+    assert!(render(&mut compositor).iter().all(|p| *p == RED));
+
+    // Hidden layers leave what is below them
+    compositor.layers[1].visible = false;
+    assert!(render(&mut compositor).iter().all(|p| *p == BLUE));
+}
+
+#[test]
+fn blends_translucent_layers_inside_their_viewport() {
+    let mut compositor = Compositor::new(WIDTH, HEIGHT);
+    compositor.push(CompositeLayer::new(Fill(RED)));
+    let left = Rect::new(0, 0, WIDTH as i32 / 2, HEIGHT as i32);
+    compositor.push(
+        CompositeLayer::new(Fill(BLUE))
+            .viewport(left)
+            .opacity(0.5f32),
+    );
+    let frame = render(&mut compositor);
+    for (i, pixel) in frame.iter().enumerate() {
+        if i as u32 % WIDTH < WIDTH / 2 {
+            // Half of each, over an opaque layer
+            assert!(pixel[0].abs_diff(128) <= 1 && pixel[2].abs_diff(128) <= 1);
+            assert_eq!((pixel[1], pixel[3]), (0, 255), "{pixel:?}");
+        } else {
+            assert_eq!(*pixel, RED);
+        }
+    }
+
+    // Adding saturates, replacing takes the alpha of the layer as well
+    compositor.layers[1].blend_mode = BlendMode::Add;
+    compositor.layers[1].opacity = 1f32;
+    assert_eq!(render(&mut compositor)[0], [255, 0, 255, 255]);
+    compositor.layers[1].blend_mode = BlendMode::Replace;
+    compositor.layers[1].opacity = 0.5f32;
+    assert_eq!(render(&mut compositor)[0], [0, 0, 255, 128]);
+}
@@ -0,0 +1,165 @@
+use glam::{Vec2, Vec3};
+use pixel_renderer::{
+    camera::Camera,
+    controller::{CameraController, CameraInput, FlyController, FpsController, OrbitController},
+};
+use radians::Rad32;
+
+const DIMENSIONS: (u32, u32) = (64, 48);
+
+fn camera() -> Camera {
+    Camera {
+        position: Vec3::new(1f32, 2f32, 3f32),
+        yaw: Rad32::new(0.3f32),
+        pitch: Rad32::new(-0.4f32),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn fly_controller_moves_along_the_camera() {
+    let mut controller = FlyController::default();
+    controller.smoothing = 0f32;
+    let mut camera = camera();
+    let start = camera;
+
+    let input = CameraInput {
+        movement: Vec3::new(1f32, 0f32, 1f32),
+        ..Default::default()
+    };
+    controller.update(&mut camera, &input, 0.5f32, DIMENSIONS);
+    let expected = start.position + (start.right() + start.forward()) * controller.speed * 0.5f32;
+    assert!(
+        camera.position.abs_diff_eq(expected, 1e-5f32),
+        "{}",
+        camera.position
+    );
+
+    // Mouse movement to the right and down turns right and down
+    let input = CameraInput {
+        look: Vec2::new(100f32, 20f32),
+        ..Default::default()
+    };
+    controller.update(&mut camera, &input, 0.5f32, DIMENSIONS);
+    assert!((camera.yaw.val() - start.yaw.val() - 0.5f32).abs() < 1e-5f32);
+    assert!((camera.pitch.val() - start.pitch.val() + 0.1f32).abs() < 1e-5f32);
+    assert!(camera.forward().dot(start.right()) > 0f32);
+    assert!(camera.forward().y < start.forward().y);
+
+    // Scrolling without a cursor moves forward
+    let position = camera.position;
+    let input = CameraInput {
+        zoom: 2f32,
+        ..Default::default()
+    };
+    controller.update(&mut camera, &input, 0.5f32, DIMENSIONS);
+    let expected = position + camera.forward() * 2f32 * controller.zoom_speed;
+    assert!(
+        camera.position.abs_diff_eq(expected, 1e-5f32),
+        "{}",
+        camera.position
+    );
+}
+
+#[test]
+fn fly_controller_smooths_the_input() {
+    let mut controller = FlyController::default();
+    let mut camera = camera();
+    let input = CameraInput {
+        look: Vec2::new(100f32, 0f32),
+        ..Default::default()
+    };
+    controller.update(&mut camera, &input, 0.01f32, DIMENSIONS);
+    let turned = camera.yaw.val() - 0.3f32;
+    assert!(turned > 0f32 && turned < 0.5f32, "{turned}");
+    // The rest of the movement follows without further input
+    for _ in 0..100 {
+        controller.update(&mut camera, &CameraInput::default(), 0.01f32, DIMENSIONS);
+    }
+    assert!((camera.yaw.val() - 0.8f32).abs() < 1e-3f32);
+}
+
+#[test]
+fn fps_controller_walks_on_the_ground() {
+    let mut controller = FpsController::default();
+    controller.fly.smoothing = 0f32;
+    let mut camera = Camera {
+        roll: Rad32::new(0.2f32),
+        ..camera()
+    };
+    let start = camera;
+
+    let input = CameraInput {
+        movement: Vec3::Z,
+        zoom: 3f32,
+        ..Default::default()
+    };
+    controller.update(&mut camera, &input, 1f32, DIMENSIONS);
+    assert_eq!(camera.roll.val(), 0f32);
+    let step = camera.position - start.position;
+    let ahead = (start.forward() * Vec3::new(1f32, 0f32, 1f32)).normalize();
+    assert!(
+        step.abs_diff_eq(ahead * controller.fly.speed, 1e-5f32),
+        "{step}"
+    );
+    assert_eq!(camera.focal_length, start.focal_length + 3f32);
+
+    let input = CameraInput {
+        zoom: -100f32,
+        ..Default::default()
+    };
+    controller.update(&mut camera, &input, 1f32, DIMENSIONS);
+    assert_eq!(camera.focal_length, controller.min_focal_length);
+}
+
+#[test]
+fn orbit_controller_circles_the_target() {
+    let target = Vec3::new(0f32, 1f32, 0f32);
+    let mut camera = camera();
+    let mut controller = OrbitController::new(&camera, target);
+    controller.smoothing = 0f32;
+    let distance = camera.position.distance(target);
+
+    // Without input the camera stays where it is and looks at the target
+    let start = camera.position;
+    controller.update(&mut camera, &CameraInput::default(), 0.1f32, DIMENSIONS);
+    assert!(
+        camera.position.abs_diff_eq(start, 1e-4f32),
+        "{}",
+        camera.position
+    );
+    let towards = (target - camera.position).normalize();
+    assert!(camera.forward().abs_diff_eq(towards, 1e-5f32));
+
+    let input = CameraInput {
+        look: Vec2::new(100f32, 0f32),
+        ..Default::default()
+    };
+    controller.update(&mut camera, &input, 0.1f32, DIMENSIONS);
+    assert!((camera.position.distance(target) - distance).abs() < 1e-4f32);
+    assert!((camera.position.y - start.y).abs() < 1e-4f32);
+    let horizontal = Vec3::new(1f32, 0f32, 1f32);
+    let turned =
+        ((start - target) * horizontal).angle_between((camera.position - target) * horizontal);
+    assert!((turned - 0.5f32).abs() < 1e-4f32, "{turned}");
+
+    // Zooming in covers a fraction of the distance per step
+    let input = CameraInput {
+        zoom: 1f32,
+        ..Default::default()
+    };
+    controller.update(&mut camera, &input, 0.1f32, DIMENSIONS);
+    let expected = distance * (1f32 - controller.zoom_speed);
+    assert!((camera.position.distance(target) - expected).abs() < 1e-4f32);
+
+    // Panning moves the target and the camera together
+    let (position, right) = (camera.position, camera.right());
+    let input = CameraInput {
+        movement: Vec3::X,
+        ..Default::default()
+    };
+    controller.update(&mut camera, &input, 0.5f32, DIMENSIONS);
+    let offset = right * controller.pan_speed * expected * 0.5f32;
+    assert!(controller.target.abs_diff_eq(target + offset, 1e-4f32));
+    assert!(camera.position.abs_diff_eq(position + offset, 1e-4f32));
+}
@@ -0,0 +1,70 @@
+use glam::{Vec2, Vec3};
+use pixel_renderer::{
+    camera::{Camera, Projection},
+    mesh::IndexedMesh,
+    renderer::{Mesh3D, Rasterizer},
+};
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 48;
+
+/// A square facing the camera `distance` in front of it, covering the left half of the output
+fn square(distance: f32) -> IndexedMesh {
+    IndexedMesh {
+        positions: vec![
+            Vec3::new(-100f32, -100f32, -distance),
+            Vec3::new(0f32, -100f32, -distance),
+            Vec3::new(0f32, 100f32, -distance),
+            Vec3::new(-100f32, 100f32, -distance),
+        ],
+        triangles: vec![(0, 1, 2), (0, 2, 3)],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn depth_values_round_trip() {
+    let perspective = Camera {
+        far: 20f32,
+        ..Default::default()
+    };
+    let orthographic = Camera {
+        projection: Projection::Orthographic { height: 100f32 },
+        ..perspective
+    };
+    let rasterizer = Rasterizer::new(WIDTH, HEIGHT);
+
+    for mut camera in [perspective, orthographic] {
+        camera.look_at(Vec3::NEG_Z, Vec3::Y);
+        let distance = 7.5f32;
+        let objects: Vec<Box<dyn Mesh3D>> = vec![Box::new(square(distance))];
+        let depth = rasterizer.depth_buffer(&camera, &objects);
+        assert_eq!((depth.width(), depth.height()), (WIDTH, HEIGHT));
+        let linearized = depth.linearized(&camera);
+
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                let i = (y * WIDTH + x) as usize;
+                assert_eq!(depth.values()[i].is_finite(), depth.get(x, y).is_some());
+                let Some(value) = depth.get(x, y) else {
+                    assert!(x >= WIDTH / 2, "({x}, {y}) is empty");
+                    assert_eq!(linearized[i], f32::INFINITY);
+                    continue;
+                };
+                assert!(x < WIDTH / 2, "({x}, {y}) is drawn");
+
+                // Stored depth is the projected depth of the point drawn at the pixel
+                let center = Vec2::new(x as f32 + 0.5f32, y as f32 + 0.5f32);
+                let point = camera.unproject(center, value, (WIDTH, HEIGHT));
+                assert!((point.z + distance).abs() < 1e-3f32, "{point}");
+                let projected = camera.project(point, (WIDTH, HEIGHT)).z;
+                assert!((projected - value).abs() < 1e-6f32);
+
+                let linear = depth.linear_depth(x, y, &camera).unwrap();
+                assert!((linear - distance).abs() < 1e-3f32, "{linear}");
+                assert_eq!(linearized[i], linear);
+            }
+        }
+    }
+    assert_eq!(rasterizer.depth_buffer(&perspective, &[]).get(0, 0), None);
+}
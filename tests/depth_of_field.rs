@@ -0,0 +1,41 @@
+use pixel_renderer::{
+    camera::{Camera, Projection},
+    renderer::DepthOfField,
+};
+
+const DIMENSIONS: (u32, u32) = (640, 480);
+
+#[test]
+fn focus_plane_is_sharp() {
+    let camera = Camera {
+        focal_length: 50f32,
+        ..Default::default()
+    };
+    let dof = DepthOfField {
+        focus_distance: 3f32,
+        ..Default::default()
+    };
+    let coc = |distance: f32| dof.circle_of_confusion(&camera, distance, DIMENSIONS);
+    assert_eq!(coc(dof.focus_distance), 0f32);
+
+    // The blur grows on both sides of the focus plane
+    assert!(coc(2.9f32) > 0f32 && coc(3.1f32) > 0f32);
+    assert!(coc(1f32) > coc(2f32));
+    assert!(coc(10f32) > coc(5f32));
+    // Opening the lens blurs more
+    let open = DepthOfField {
+        f_stop: dof.f_stop / 2f32,
+        ..dof
+    };
+    let wide = open.circle_of_confusion(&camera, 1f32, DIMENSIONS);
+    assert!((wide - coc(1f32) * 2f32).abs() < 1e-3f32 * wide, "{wide}");
+
+    let orthographic = Camera {
+        projection: Projection::Orthographic { height: 2f32 },
+        ..camera
+    };
+    assert_eq!(
+        dof.circle_of_confusion(&orthographic, 1f32, DIMENSIONS),
+        0f32
+    );
+}
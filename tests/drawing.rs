@@ -0,0 +1,72 @@
+use palette::Srgba;
+use pixel_renderer::{
+    camera::Camera,
+    drawing::{BresenhamCircle, Circle, Shape2D},
+    renderer::{Background, Drawifier, Renderer, Scene2D},
+};
+
+const WIDTH: u32 = 32;
+const HEIGHT: u32 = 32;
+
+fn render(shapes: &[Shape2D]) -> Vec<u8> {
+    let drawifier = Drawifier {
+        output_width: WIDTH,
+        output_height: HEIGHT,
+        background: Default::default(),
+    };
+    let mut buffer = vec![0u8; (WIDTH * HEIGHT * 4) as usize];
+    let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+    drawifier.render(&Camera::default(), shapes, &mut frame);
+    buffer
+}
+
+#[test]
+fn collected_pixels_draw_like_single_pixels() {
+    // Overlapping translucent circles in alternating colors
+    let colors = [
+        Srgba::new(1f32, 0f32, 0f32, 0.5f32),
+        Srgba::new(0f32, 0f32, 1f32, 0.7f32),
+    ];
+    let pixels: Vec<_> = (0..6)
+        .flat_map(|i| BresenhamCircle::new((16, 16), 4 + i % 3, colors[i as usize % 2]))
+        .collect();
+
+    let single: Vec<Shape2D> = pixels.iter().map(|p| Shape2D::Pixel(*p)).collect();
+    let collected: Shape2D = pixels.iter().copied().collect();
+    assert!(matches!(&collected, Shape2D::Compound(groups) if groups.len() == 6));
+    assert!(render(&[collected]) == render(&single));
+
+    let circle: Shape2D = BresenhamCircle::new((16, 16), 8, colors[0]).collect();
+    assert!(matches!(circle, Shape2D::Spans { .. }));
+}
+
+#[test]
+fn kept_background_is_restored_under_moved_shapes() {
+    let drawifier = Drawifier {
+        output_width: WIDTH,
+        output_height: HEIGHT,
+        background: Background::Keep,
+    };
+    let pattern: Vec<u8> = (0..WIDTH * HEIGHT * 4)
+        .map(|i| (i * 7 % 251) as u8)
+        .collect();
+    let red = Srgba::new(1f32, 0f32, 0f32, 1f32);
+    let render_dirty = |scene: &mut Scene2D, buffer: &mut Vec<u8>| {
+        let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+        drawifier.render_dirty(scene, &mut frame);
+    };
+
+    let mut scene = Scene2D::new();
+    let circle = scene.add(BresenhamCircle::new((8, 8), 4, red).collect());
+    let mut moved = pattern.clone();
+    render_dirty(&mut scene, &mut moved);
+    assert!(moved != pattern);
+    scene.update(circle, BresenhamCircle::new((20, 20), 4, red).collect());
+    render_dirty(&mut scene, &mut moved);
+
+    let mut expected = Scene2D::new();
+    expected.add(BresenhamCircle::new((20, 20), 4, red).collect());
+    let mut drawn = pattern.clone();
+    render_dirty(&mut expected, &mut drawn);
+    assert!(moved == drawn);
+}
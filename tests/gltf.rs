@@ -0,0 +1,215 @@
+use glam::Vec3;
+use palette::Srgba;
+use pixel_renderer::{
+    camera::{Camera, Projection},
+    formats::{GltfLoadError, GltfScene},
+    renderer::{Mesh3D, Rasterizer, Renderer},
+};
+
+const DATA: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data");
+
+fn close(a: Vec3, b: Vec3) -> bool {
+    a.abs_diff_eq(b, 1e-5)
+}
+
+fn rgba(c: Srgba) -> [u8; 4] {
+    c.into_format().into()
+}
+
+fn check_triangle(scene: &GltfScene) {
+    assert_eq!(scene.meshes.len(), 1);
+    assert_eq!(scene.meshes[0].name, "triangle");
+    let mesh = &scene.meshes[0].mesh;
+    assert_eq!(mesh.triangles, vec![(0, 1, 2)]);
+
+    // Translations of the parent and the child node add up
+    let expected = [
+        Vec3::new(1f32, 0f32, -1f32),
+        Vec3::new(2f32, 0f32, -1f32),
+        Vec3::new(1f32, 1f32, -1f32),
+    ];
+    for (p, e) in mesh.positions.iter().zip(expected) {
+        assert!(close(*p, e), "{p} != {e}");
+    }
+    assert_eq!(
+        mesh.colors.iter().map(|c| rgba(*c)).collect::<Vec<_>>(),
+        vec![[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]]
+    );
+
+    assert_eq!(scene.cameras.len(), 1);
+    let camera = &scene.cameras[0];
+    assert!(close(camera.position, Vec3::new(0f32, 0f32, 2f32)));
+    assert!(close(camera.forward(), Vec3::NEG_Z));
+    assert!(close(camera.up(), Vec3::Y));
+    assert_eq!(camera.projection, Projection::Perspective);
+    assert_eq!(camera.aperture, (36, 24));
+    let yfov = 2f32 * (camera.aperture.1 as f32 / 2f32 / camera.focal_length).atan();
+    assert!((yfov - 0.8f32).abs() < 1e-5);
+    assert_eq!((camera.near, camera.far), (0.1f32, 100f32));
+}
+
+#[test]
+fn loads_gltf_with_embedded_buffer() {
+    let scene = GltfScene::load(format!("{DATA}/triangle.gltf")).unwrap();
+    check_triangle(&scene);
+}
+
+#[test]
+fn loads_glb() {
+    let scene = GltfScene::load(format!("{DATA}/triangle.glb")).unwrap();
+    check_triangle(&scene);
+}
+
+#[test]
+fn loads_external_buffer_and_base_color_texture() {
+    let scene = GltfScene::load(format!("{DATA}/textured.gltf")).unwrap();
+    assert_eq!(scene.meshes.len(), 1);
+    assert!(scene.cameras.is_empty());
+
+    let mesh = &scene.meshes[0].mesh;
+    assert_eq!(mesh.triangles, vec![(0, 1, 2), (0, 2, 3)]);
+    // The texture is sampled per pixel, the colors are the base color factor
+    assert_eq!(
+        mesh.colors.iter().map(|c| rgba(*c)).collect::<Vec<_>>(),
+        vec![[255, 255, 255, 128]; 4]
+    );
+    // Every corner samples its texel, with v = 0 at the top of the image
+    let texture = mesh.texture.as_ref().unwrap();
+    assert_eq!(
+        mesh.tex_coords
+            .iter()
+            .map(|uv| rgba(texture.sample(*uv)))
+            .collect::<Vec<_>>(),
+        vec![
+            [0, 0, 255, 255],
+            [255, 255, 255, 255],
+            [0, 255, 0, 255],
+            [255, 0, 0, 255]
+        ]
+    );
+}
+
+#[test]
+fn converts_linear_colors_to_srgb() {
+    let gltf = std::fs::read_to_string(format!("{DATA}/textured.gltf")).unwrap();
+    let darker = gltf.replace(
+        r#""baseColorFactor": [
+          1,
+          1,
+          1,"#,
+        r#""baseColorFactor": [
+          0.5,
+          0.2,
+          1,"#,
+    );
+    assert_ne!(darker, gltf);
+    let scene = GltfScene::from_slice(darker.as_bytes(), DATA).unwrap();
+    // Half the light is brighter than half the sRGB value, alpha is not converted
+    assert_eq!(rgba(scene.meshes[0].mesh.colors[0]), [188, 124, 255, 128]);
+}
+
+#[test]
+fn samples_the_base_color_texture_per_pixel() {
+    let scene = GltfScene::load(format!("{DATA}/textured.gltf")).unwrap();
+    let mut camera = Camera {
+        projection: Projection::Orthographic { height: 1f32 },
+        aperture: (24, 24),
+        position: Vec3::new(0.5f32, 0.5f32, 1f32),
+        ..Default::default()
+    };
+    camera.look_at(Vec3::new(0.5f32, 0.5f32, 0f32), Vec3::Y);
+    let rasterizer = Rasterizer::new(32, 32);
+    // Only the first triangle, the lower right half of the square
+    let mut triangle = scene.meshes[0].clone();
+    triangle.mesh.triangles.truncate(1);
+    let objects: Vec<Box<dyn Mesh3D>> = vec![Box::new(triangle)];
+    let mut buffer = vec![0u8; 32 * 32 * 4];
+    let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+    rasterizer.render(&camera, &objects, &mut frame);
+
+    // The corners of the triangle show three different texels at half opacity
+    let pixel = |x: usize, y: usize| buffer[(y * 32 + x) * 4..][..3].to_vec();
+    let half = |c: [u8; 3]| c.map(|c| c / 2);
+    for ((x, y), expected) in [
+        ((28, 4), half([0, 255, 0])),
+        ((4, 28), half([0, 0, 255])),
+        ((28, 28), half([255, 255, 255])),
+        ((4, 4), [0, 0, 0]),
+    ] {
+        let pixel = pixel(x, y);
+        assert!(
+            pixel.iter().zip(expected).all(|(a, b)| a.abs_diff(b) <= 1),
+            "{pixel:?} at ({x}, {y})"
+        );
+    }
+}
+
+#[test]
+fn keeps_mirrored_nodes_facing_the_camera() {
+    let gltf = std::fs::read_to_string(format!("{DATA}/triangle.gltf")).unwrap();
+    let mirrored = gltf.replace(r#""mesh": 0,"#, r#""mesh": 0, "scale": [-1, 1, 1],"#);
+    assert_ne!(mirrored, gltf);
+    for gltf in [gltf, mirrored] {
+        let scene = GltfScene::from_slice(gltf.as_bytes(), DATA).unwrap();
+        let mesh = &scene.meshes[0].mesh;
+        // The normal of the triangle still points at the camera
+        let normal = mesh.face_normal(mesh.triangles[0]);
+        assert!(normal.abs_diff_eq(Vec3::Z, 1e-5), "{normal}");
+        let mut buffer = vec![0u8; 48 * 32 * 4];
+        let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+        let world = scene.into_world(Rasterizer::new(48, 32));
+        world
+            .renderer
+            .render(&world.camera, &world.objects, &mut frame);
+        assert!(frame.iter().any(|p| p[..3] != [0, 0, 0]));
+    }
+}
+
+#[test]
+fn rejects_required_extensions() {
+    let error = GltfScene::load(format!("{DATA}/draco.gltf")).unwrap_err();
+    assert!(matches!(
+        &error,
+        GltfLoadError::UnsupportedExtension(name) if name == "KHR_draco_mesh_compression"
+    ));
+    assert_eq!(
+        error.to_string(),
+        "required glTF extension KHR_draco_mesh_compression is not supported"
+    );
+}
+
+#[test]
+fn rejects_buffer_views_past_their_buffer() {
+    let gltf = r#"{
+        "asset": { "version": "2.0" },
+        "buffers": [{ "byteLength": 4, "uri": "data:application/octet-stream;base64,AAAAAA==" }],
+        "bufferViews": [{ "buffer": 0, "byteOffset": 2, "byteLength": 64 }],
+        "images": [{ "bufferView": 0, "mimeType": "image/png" }]
+    }"#;
+    let error = GltfScene::from_slice(gltf.as_bytes(), DATA).unwrap_err();
+    assert!(matches!(error, GltfLoadError::MissingData(_)), "{error}");
+    assert_eq!(
+        error.to_string(),
+        "missing glTF data: buffer view 0 runs past its buffer"
+    );
+}
+
+#[test]
+fn reports_missing_files() {
+    let error = GltfScene::load(format!("{DATA}/missing.gltf")).unwrap_err();
+    assert!(matches!(error, GltfLoadError::Io { .. }));
+}
+
+#[test]
+fn renders_through_the_scene_camera() {
+    let scene = GltfScene::load(format!("{DATA}/triangle.glb")).unwrap();
+    let world = scene.into_world(Rasterizer::new(48, 32));
+    assert_eq!(world.objects.len(), 1);
+
+    let mut buffer = vec![0u8; 48 * 32 * 4];
+    let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+    world
+        .renderer
+        .render(&world.camera, &world.objects, &mut frame);
+    assert!(frame.iter().any(|p| p[..3] != [0, 0, 0]));
+}
@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+use glam::{Vec2, Vec3};
+use pixel_renderer::{
+    formats::{read_stl, write_stl, StlFormat},
+    mesh::IndexedMesh,
+    primitives::{Cube, Icosphere, Plane, UvSphere},
+    renderer::{AttributeBuffer, Semantic},
+};
+use radians::Rad32;
+
+/// Whether every edge between positions is shared by exactly two triangles
+fn is_closed(mesh: &IndexedMesh) -> bool {
+    let mut welded = IndexedMesh {
+        positions: mesh.positions.clone(),
+        triangles: mesh.triangles.clone(),
+        ..Default::default()
+    };
+    welded.weld(0f32);
+    let mut edges: HashMap<(usize, usize), u32> = HashMap::new();
+    for t in &welded.triangles {
+        for (u, v) in [(t.0, t.1), (t.1, t.2), (t.2, t.0)] {
+            *edges.entry((u.min(v), u.max(v))).or_default() += 1;
+        }
+    }
+    edges.values().all(|n| *n == 2)
+}
+
+fn volume(mesh: &IndexedMesh) -> f32 {
+    let p = &mesh.positions;
+    mesh.triangles
+        .iter()
+        .map(|t| p[t.0].dot(p[t.1].cross(p[t.2])) / 6f32)
+        .sum()
+}
+
+#[test]
+fn welds_triangle_soups() {
+    let mut bytes = vec![];
+    write_stl(&Cube::default().mesh(), StlFormat::Binary, &mut bytes).unwrap();
+    let mut cube = read_stl(&bytes[..]).unwrap();
+    assert_eq!(cube.positions.len(), 36);
+
+    // Facet normals keep the faces apart
+    cube.weld(1e-5f32);
+    assert_eq!(cube.positions.len(), 24);
+
+    cube.normals.clear();
+    for (i, p) in cube.positions.iter_mut().enumerate() {
+        p.x += i as f32 * 1e-7f32;
+    }
+    cube.weld(1e-5f32);
+    assert_eq!(cube.positions.len(), 8);
+    assert_eq!(cube.triangles.len(), 12);
+    assert!(is_closed(&cube));
+}
+
+#[test]
+fn removes_degenerate_triangles() {
+    let mut mesh = IndexedMesh {
+        positions: vec![
+            Vec3::ZERO,
+            Vec3::X,
+            Vec3::Y,
+            Vec3::X * 2f32,
+            Vec3::ZERO,
+            Vec3::Z,
+        ],
+        tex_coords: vec![Vec2::ZERO; 6],
+        triangles: vec![(0, 1, 2), (0, 0, 2), (0, 1, 3), (0, 4, 5), (2, 1, 5)],
+        ..Default::default()
+    };
+    mesh.remove_degenerate_triangles();
+    assert_eq!(mesh.triangles, vec![(0, 1, 2), (2, 1, 3)]);
+    assert_eq!(mesh.positions, vec![Vec3::ZERO, Vec3::X, Vec3::Y, Vec3::Z]);
+    assert_eq!(mesh.tex_coords.len(), 4);
+}
+
+#[test]
+fn empty_extra_attributes_stay_empty() {
+    let mut mesh = UvSphere::default().mesh();
+    mesh.extra = vec![(Semantic::TexCoord(1), AttributeBuffer::Vec2(vec![]))];
+    mesh.compute_normals(Rad32::new(0.5f32));
+    mesh.compute_tangents();
+    mesh.weld(1e-5f32);
+    mesh.remove_degenerate_triangles();
+    mesh.remove_unused_vertices();
+    mesh.simplify(mesh.triangles.len() / 2);
+    assert!(!mesh.triangles.is_empty());
+    assert!(matches!(
+        mesh.extra.first(),
+        Some((Semantic::TexCoord(1), AttributeBuffer::Vec2(v))) if v.is_empty()
+    ));
+}
+
+#[test]
+fn simplifies_to_the_target() {
+    let mut sphere = UvSphere {
+        segments: 64,
+        rings: 32,
+        ..Default::default()
+    }
+    .mesh();
+    let triangles = sphere.triangles.len();
+    let expected = volume(&sphere);
+    sphere.simplify(triangles / 10);
+    assert!(sphere.triangles.len() <= triangles / 10);
+    assert!(sphere.triangles.len() > triangles / 20);
+    assert!((volume(&sphere) / expected - 1f32).abs() < 0.05f32);
+    // No triangle was flipped over and the seam did not open
+    for t in &sphere.triangles {
+        let center = (sphere.positions[t.0] + sphere.positions[t.1] + sphere.positions[t.2]) / 3f32;
+        assert!(sphere.face_normal(*t).dot(center) > 0f32);
+    }
+    assert!(is_closed(&sphere));
+    assert_eq!(sphere.normals.len(), sphere.positions.len());
+
+    // A flat grid needs nothing but its corners
+    let mut plane = Plane {
+        subdivisions: (16, 16),
+        ..Default::default()
+    }
+    .mesh();
+    plane.simplify(2);
+    assert_eq!(plane.triangles.len(), 2);
+    for p in &plane.positions {
+        assert_eq!(p.abs(), Vec3::new(0.5f32, 0f32, 0.5f32));
+    }
+}
+
+#[test]
+fn subdivides_smoothly() {
+    let mut sphere = Icosphere {
+        subdivisions: 0,
+        ..Default::default()
+    }
+    .mesh();
+    for _ in 0..3 {
+        sphere.subdivide();
+    }
+    assert_eq!(sphere.triangles.len(), 20 * 64);
+    assert!(is_closed(&sphere));
+    // Loop subdivision shrinks the icosahedron towards a sphere inside it
+    let radii: Vec<f32> = sphere.positions.iter().map(|p| p.length()).collect();
+    let (min, max) = radii
+        .iter()
+        .fold((f32::MAX, 0f32), |(min, max), r| (min.min(*r), max.max(*r)));
+    assert!(max < 0.5f32 && max - min < 0.01f32, "{min} to {max}");
+    for (p, n) in sphere.positions.iter().zip(&sphere.normals) {
+        assert!(n.abs_diff_eq(p.normalize(), 0.05f32), "{n} at {p}");
+    }
+
+    // Flat meshes stay flat and texture coordinates are interpolated
+    let mut plane = Plane::default().mesh();
+    plane.subdivide();
+    assert_eq!(plane.triangles.len(), 8);
+    assert!(plane.positions.iter().all(|p| p.y == 0f32));
+    assert_eq!(plane.tex_coords.len(), 9);
+    assert!(plane.tex_coords.contains(&Vec2::splat(0.5f32)));
+}
+
+#[test]
+fn subdividing_rounds_off_creases() {
+    let mut cube = Cube::default().mesh();
+    cube.subdivide();
+    cube.subdivide();
+    assert!(is_closed(&cube));
+    // Vertices split for the texture share their normal, which points away from the center
+    let mut normals: HashMap<[u32; 3], Vec3> = HashMap::new();
+    for (p, n) in cube.positions.iter().zip(&cube.normals) {
+        assert!(n.dot(*p) > 0f32, "{n} at {p}");
+        let shared = normals.entry(p.to_array().map(f32::to_bits)).or_insert(*n);
+        assert!(shared.abs_diff_eq(*n, 1e-5f32), "{n} and {shared} at {p}");
+    }
+    assert!(normals.len() < cube.positions.len());
+}
+
+#[test]
+fn subdividing_recomputes_tangents() {
+    let mut plane = Plane::default().mesh();
+    plane.compute_tangents();
+    plane
+        .extra
+        .push((Semantic::TexCoord(1), AttributeBuffer::Vec2(vec![])));
+    plane.subdivide();
+    let tangents = plane
+        .extra
+        .iter()
+        .find_map(|(semantic, buffer)| match buffer {
+            AttributeBuffer::Vec4(v) if *semantic == Semantic::Tangent => Some(v),
+            _ => None,
+        });
+    let tangents = tangents.unwrap();
+    assert_eq!(tangents.len(), plane.positions.len());
+    for t in tangents {
+        assert!((t.truncate().length() - 1f32).abs() < 1e-5f32, "{t}");
+        assert_eq!(t.w.abs(), 1f32);
+    }
+    // Empty attributes stay empty
+    assert!(plane
+        .extra
+        .iter()
+        .any(|(_, buffer)| matches!(buffer, AttributeBuffer::Vec2(v) if v.is_empty())));
+
+    // Without texture coordinates there is nothing to compute them from
+    plane.tex_coords.clear();
+    plane.subdivide();
+    assert!(plane.extra.iter().all(|(s, _)| *s != Semantic::Tangent));
+}
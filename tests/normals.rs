@@ -0,0 +1,103 @@
+use std::f32::consts::PI;
+
+use glam::{Vec2, Vec3, Vec4};
+use pixel_renderer::{
+    mesh::IndexedMesh,
+    primitives::{Plane, UvSphere},
+    renderer::{AttributeBuffer, Semantic},
+};
+use radians::Rad32;
+
+/// Unit cube whose faces share their 8 corners
+fn welded_cube() -> IndexedMesh {
+    let positions = (0..8)
+        .map(|i| Vec3::new((i & 1) as f32, (i >> 1 & 1) as f32, (i >> 2) as f32) - 0.5f32)
+        .collect();
+    let quads = [
+        [0, 2, 3, 1],
+        [4, 5, 7, 6],
+        [0, 1, 5, 4],
+        [2, 6, 7, 3],
+        [0, 4, 6, 2],
+        [1, 3, 7, 5],
+    ];
+    IndexedMesh {
+        positions,
+        triangles: quads
+            .iter()
+            .flat_map(|q| [(q[0], q[1], q[2]), (q[0], q[2], q[3])])
+            .collect(),
+        ..Default::default()
+    }
+}
+
+fn tangents(mesh: &IndexedMesh) -> &[Vec4] {
+    match mesh.extra.iter().find(|(s, _)| *s == Semantic::Tangent) {
+        Some((_, AttributeBuffer::Vec4(tangents))) => tangents,
+        _ => panic!("no tangents"),
+    }
+}
+
+#[test]
+fn splits_hard_edges() {
+    let mut cube = welded_cube();
+    cube.compute_normals(Rad32::new(PI / 6f32));
+    assert_eq!(cube.positions.len(), 24);
+    for (i, t) in cube.triangles.iter().enumerate() {
+        let face = cube.face_normal(*t);
+        assert_eq!(face.abs().max_element(), 1f32, "triangle {i}");
+        for v in [t.0, t.1, t.2] {
+            assert_eq!(cube.normals[v], face);
+        }
+    }
+
+    let mut cube = welded_cube();
+    cube.compute_normals(Rad32::new(PI));
+    assert_eq!(cube.positions.len(), 8);
+    for (p, n) in cube.positions.iter().zip(&cube.normals) {
+        assert!(n.abs_diff_eq(p.normalize(), 1e-5f32), "{n} at {p}");
+    }
+}
+
+#[test]
+fn smooths_spheres_across_the_seam() {
+    let mut sphere = UvSphere::default().mesh();
+    sphere.normals.clear();
+    sphere.compute_normals(Rad32::new(PI / 4f32));
+    for (p, n) in sphere.positions.iter().zip(&sphere.normals) {
+        assert!(n.abs_diff_eq(p.normalize(), 2e-2f32), "{n} at {p}");
+    }
+}
+
+#[test]
+fn tangents_follow_the_texture() {
+    // The plane faces +Y with u along +X and v along -Z
+    let mut plane = Plane::default().mesh();
+    plane.compute_tangents();
+    for tangent in tangents(&plane) {
+        assert!(tangent.abs_diff_eq(Vec4::new(1f32, 0f32, 0f32, 1f32), 1e-5f32));
+    }
+
+    // Mirrored texture on one half of the plane
+    let mut plane = Plane {
+        subdivisions: (2, 1),
+        ..Default::default()
+    }
+    .mesh();
+    plane.normals.clear();
+    for (p, uv) in plane.positions.iter().zip(&mut plane.tex_coords) {
+        *uv = Vec2::new(p.x.abs(), uv.y);
+    }
+    plane.compute_tangents();
+    assert_eq!(plane.normals.len(), plane.positions.len());
+    let tangents = tangents(&plane);
+    // The middle column is split in two
+    assert_eq!(tangents.len(), 8);
+    for (p, tangent) in plane.positions.iter().zip(tangents) {
+        if p.x > 0f32 {
+            assert_eq!(*tangent, Vec4::new(1f32, 0f32, 0f32, 1f32));
+        } else if p.x < 0f32 {
+            assert_eq!(*tangent, Vec4::new(-1f32, 0f32, 0f32, -1f32));
+        }
+    }
+}
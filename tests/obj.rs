@@ -0,0 +1,119 @@
+use std::path::Path;
+
+use glam::Vec3;
+use palette::{Srgb, Srgba};
+use pixel_renderer::{
+    camera::{Camera, Projection},
+    formats::{ObjLoadError, ObjModel, TextureError},
+    renderer::{Mesh3D, Rasterizer, Renderer},
+};
+
+const DATA: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data");
+
+fn rgba(c: Srgba) -> [u8; 4] {
+    c.into_format().into()
+}
+
+#[test]
+fn loads_materials_from_the_library() {
+    let model = ObjModel::load(format!("{DATA}/materials.obj")).unwrap();
+    assert_eq!(model.name.as_deref(), Some("square"));
+
+    // Materials are numbered in the order the faces use them, after the default one
+    let names: Vec<_> = model.materials.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, ["", "checker", "red"]);
+    let red = &model.materials[2];
+    assert_eq!(red.diffuse, Srgb::new(1f32, 0f32, 0f32));
+    assert_eq!(red.specular, Srgb::new(0.5f32, 0.5f32, 0.5f32));
+    assert_eq!((red.shininess, red.opacity), (10f32, 0.5f32));
+    assert!(red.diffuse_map.is_none());
+    let checker = model.materials[1].diffuse_map.as_ref().unwrap();
+    assert_eq!((checker.width(), checker.height()), (2, 2));
+
+    // One mesh for every material of every group, in the order of their first faces
+    let meshes: Vec<_> = model
+        .meshes
+        .iter()
+        .map(|m| (m.name.as_str(), m.material, m.mesh.triangles.len()))
+        .collect();
+    assert_eq!(
+        meshes,
+        [("textured", 1, 1), ("plain", 2, 1), ("textured", 2, 1)]
+    );
+    for mesh in &model.meshes {
+        let material = &model.materials[mesh.material];
+        assert_eq!(mesh.mesh.positions.len(), 3);
+        assert_eq!(mesh.mesh.normals, vec![Vec3::Z; 3]);
+        assert_eq!(mesh.mesh.tex_coords.len(), 3);
+        let d = material.diffuse;
+        let color = Srgba::new(d.red, d.green, d.blue, material.opacity);
+        assert_eq!(mesh.mesh.colors, vec![color; 3]);
+        assert_eq!(mesh.mesh.texture, material.diffuse_map);
+    }
+}
+
+#[test]
+fn samples_the_diffuse_map_per_pixel() {
+    let model = ObjModel::load(format!("{DATA}/materials.obj")).unwrap();
+    let mut camera = Camera {
+        projection: Projection::Orthographic { height: 2f32 },
+        aperture: (24, 24),
+        position: Vec3::Z,
+        ..Default::default()
+    };
+    camera.look_at(Vec3::ZERO, Vec3::Y);
+    let rasterizer = Rasterizer::new(32, 32);
+    // Only the textured triangle, the lower right half of the square
+    let objects: Vec<Box<dyn Mesh3D>> = model.into_renderables().into_iter().take(1).collect();
+    let mut buffer = vec![0u8; 32 * 32 * 4];
+    let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+    rasterizer.render(&camera, &objects, &mut frame);
+
+    // The corners of the triangle show three different texels
+    let pixel = |x: usize, y: usize| &buffer[(y * 32 + x) * 4..][..4];
+    assert_eq!(pixel(28, 4), rgba(Srgba::new(0f32, 1f32, 0f32, 1f32)));
+    assert_eq!(pixel(4, 28), rgba(Srgba::new(0f32, 0f32, 1f32, 1f32)));
+    assert_eq!(pixel(28, 28), [255; 4]);
+    assert_eq!(pixel(4, 4), [0, 0, 0, 255]);
+}
+
+fn load(obj: &str) -> Result<ObjModel, ObjLoadError> {
+    ObjModel::from_reader(obj.as_bytes(), DATA)
+}
+
+const TRIANGLE: &str = "v 0 0 0\nv 1 0 0\nv 0 1 0\n";
+
+#[test]
+fn reports_what_went_wrong() {
+    let error = load(&format!(
+        "mtllib materials.mtl\n{TRIANGLE}usemtl blue\nf 1 2 3\n"
+    ))
+    .unwrap_err();
+    assert!(matches!(&error, ObjLoadError::MissingMaterial(name) if name == "blue"));
+    assert_eq!(error.to_string(), "material blue is not defined");
+
+    let error = load("mtllib missing.mtl\n").unwrap_err();
+    assert!(
+        matches!(&error, ObjLoadError::Io { path, .. } if path == &Path::new(DATA).join("missing.mtl"))
+    );
+
+    let error = load("mtllib missing_texture.mtl\n").unwrap_err();
+    assert!(matches!(
+        &error,
+        ObjLoadError::Texture { path, error: TextureError::Io(_) }
+            if path == &Path::new(DATA).join("missing.png")
+    ));
+
+    let error = load("mtllib spectral.mtl\n").unwrap_err();
+    assert!(matches!(&error, ObjLoadError::Unsupported(what) if what.contains("daylight.rfl")));
+
+    let error = load("v 0 zero 0\n").unwrap_err();
+    assert!(matches!(error, ObjLoadError::Parse(_)));
+
+    // Faces without a material use the default one
+    let model = load(&format!("{TRIANGLE}f 1 2 3\n")).unwrap();
+    assert_eq!(model.meshes.len(), 1);
+    assert_eq!(model.meshes[0].name, "default");
+    assert_eq!(model.meshes[0].material, 0);
+    assert!(model.meshes[0].mesh.normals.is_empty());
+}
@@ -0,0 +1,83 @@
+use glam::Vec3;
+use palette::Srgba;
+use pixel_renderer::{
+    camera::Camera,
+    mesh::IndexedMesh,
+    primitives::{Cube, Torus, UvSphere},
+    renderer::{IdBuffer, Mesh3D, Rasterizer, Transparency},
+};
+use rayon::ThreadPoolBuilder;
+
+/// Several tiles wide, so the tiles are shaded by different threads
+const WIDTH: u32 = 256;
+const HEIGHT: u32 = 192;
+
+fn moved(mut mesh: IndexedMesh, offset: Vec3) -> IndexedMesh {
+    for p in &mut mesh.positions {
+        *p += offset;
+    }
+    mesh
+}
+
+/// Overlapping opaque and translucent meshes with wireframes
+fn render(transparency: Transparency) -> (Vec<u8>, IdBuffer) {
+    let mut camera = Camera {
+        position: Vec3::new(0.5f32, 1f32, 4f32),
+        ..Default::default()
+    };
+    camera.look_at(Vec3::ZERO, Vec3::Y);
+    let torus = Torus {
+        color: Srgba::new(0.9f32, 0.6f32, 0.2f32, 1f32),
+        ..Default::default()
+    };
+    let sphere = UvSphere {
+        color: Srgba::new(0.2f32, 0.4f32, 1f32, 0.5f32),
+        ..Default::default()
+    };
+    let cube = Cube {
+        color: Srgba::new(0.3f32, 1f32, 0.3f32, 0.4f32),
+        ..Default::default()
+    };
+    let objects: Vec<Box<dyn Mesh3D>> = vec![
+        Box::new(torus.mesh()),
+        Box::new(moved(sphere.mesh(), Vec3::new(0.3f32, 0f32, 0.4f32))),
+        Box::new(moved(cube.mesh(), Vec3::new(-0.4f32, 0.2f32, 0.2f32))),
+    ];
+    let rasterizer = Rasterizer {
+        show_wireframe: true,
+        transparency,
+        ..Rasterizer::new(WIDTH, HEIGHT)
+    };
+
+    let mut buffer = vec![0u8; (WIDTH * HEIGHT * 4) as usize];
+    let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+    let mut ids = IdBuffer::new(WIDTH, HEIGHT);
+    rasterizer.render_with_ids(&camera, &objects, &mut frame, &mut ids);
+    (buffer, ids)
+}
+
+#[test]
+fn frames_do_not_depend_on_the_thread_count() {
+    for transparency in [
+        Transparency::SortObjects,
+        Transparency::SortTriangles,
+        Transparency::WeightedBlended,
+    ] {
+        let in_pool = |threads: usize| {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            pool.install(|| render(transparency))
+        };
+        let (single, single_ids) = in_pool(1);
+        let (many, many_ids) = in_pool(8);
+        assert!(single.chunks_exact(4).any(|p| p != [0, 0, 0, 255]));
+        assert!(single == many, "{transparency:?}");
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                assert_eq!(single_ids.query(x, y), many_ids.query(x, y));
+            }
+        }
+    }
+}
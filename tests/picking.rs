@@ -0,0 +1,143 @@
+use glam::{Vec2, Vec3};
+use pixel_renderer::{
+    camera::Camera,
+    mesh::IndexedMesh,
+    renderer::{DepthBuffer, IdBuffer, Mesh3D, Pick, Rasterizer},
+};
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 48;
+
+/// Checks that the barycentrics of `pick` weigh `corners` to the point drawn at `center`
+fn assert_on_triangle(camera: &Camera, corners: [Vec3; 3], pick: Pick, center: Vec2) {
+    let b = pick.barycentrics;
+    assert!((b.x + b.y + b.z - 1f32).abs() < 1e-5f32, "{b}");
+    assert!(b.min_element() >= 0f32, "{b}");
+    let point = corners[0] * b.x + corners[1] * b.y + corners[2] * b.z;
+    let drawn = camera.unproject(center, pick.depth, (WIDTH, HEIGHT));
+    assert!(
+        point.abs_diff_eq(drawn, 1e-3f32),
+        "{point} instead of {drawn}"
+    );
+}
+
+#[test]
+fn picks_the_closest_triangle() {
+    let mut camera = Camera {
+        focal_length: 25f32,
+        ..Default::default()
+    };
+    camera.look_at(Vec3::NEG_Z, Vec3::Y);
+
+    let back = [
+        Vec3::new(-1f32, -1f32, -5f32),
+        Vec3::new(1f32, -1f32, -5f32),
+        Vec3::new(0f32, 1f32, -5f32),
+    ];
+    // The second triangle of the second mesh covers the right half of the first mesh
+    let front = [
+        Vec3::new(0f32, -1f32, -4f32),
+        Vec3::new(1.5f32, -1f32, -4f32),
+        Vec3::new(0f32, 1f32, -4f32),
+    ];
+    let hidden = [
+        Vec3::new(-0.1f32, 0f32, -6f32),
+        Vec3::new(0.1f32, 0f32, -6f32),
+        Vec3::new(0f32, 0.1f32, -6f32),
+    ];
+    let objects: Vec<Box<dyn Mesh3D>> = vec![
+        Box::new(IndexedMesh {
+            positions: back.to_vec(),
+            triangles: vec![(0, 1, 2)],
+            ..Default::default()
+        }),
+        Box::new(IndexedMesh {
+            positions: [hidden, front].concat(),
+            triangles: vec![(0, 1, 2), (3, 4, 5)],
+            ..Default::default()
+        }),
+    ];
+
+    let rasterizer = Rasterizer::new(WIDTH, HEIGHT);
+    let mut buffer = vec![0u8; (WIDTH * HEIGHT * 4) as usize];
+    let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+    let mut ids = IdBuffer::new(1, 1);
+    rasterizer.render_with_ids(&camera, &objects, &mut frame, &mut ids);
+    let depth: DepthBuffer = rasterizer.depth_buffer(&camera, &objects);
+
+    let dimensions = (WIDTH, HEIGHT);
+    let (mut back_picks, mut front_picks) = (0, 0);
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let pick = ids.query(x, y);
+            assert_eq!(pick.map(|p| p.depth), depth.get(x, y));
+            let Some(pick) = pick else {
+                continue;
+            };
+            let center = Vec2::new(x as f32 + 0.5f32, y as f32 + 0.5f32);
+            let corners = match (pick.object_index, pick.triangle_index) {
+                (0, 0) => {
+                    back_picks += 1;
+                    // Only the part of the back triangle left of the front one is visible
+                    let point = camera.unproject(center, pick.depth, dimensions);
+                    assert!(point.x < 0.01f32, "{point}");
+                    back
+                }
+                (1, 1) => {
+                    front_picks += 1;
+                    front
+                }
+                other => panic!("{other:?} picked at ({x}, {y})"),
+            };
+
+            assert_on_triangle(&camera, corners, pick, center);
+        }
+    }
+    assert!(
+        back_picks > 50 && front_picks > 50,
+        "{back_picks} and {front_picks}"
+    );
+
+    // Picking without drawing finds the same triangles
+    let picked = rasterizer.pick_buffer(&camera, &objects);
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            assert_eq!(picked.query(x, y), ids.query(x, y));
+        }
+    }
+    assert_eq!(ids.query(WIDTH, 0), None);
+}
+
+#[test]
+fn barycentrics_are_perspective_correct() {
+    let mut camera = Camera {
+        position: Vec3::new(0.5f32, 1f32, 2f32),
+        focal_length: 25f32,
+        ..Default::default()
+    };
+    camera.look_at(Vec3::new(0f32, 0f32, -3f32), Vec3::Y);
+    // Tilted away from the camera, so weights in raster space would be off
+    let corners = [
+        Vec3::new(-1.5f32, -0.5f32, -2f32),
+        Vec3::new(1.5f32, -0.8f32, -3f32),
+        Vec3::new(0f32, 1.2f32, -8f32),
+    ];
+    let objects: Vec<Box<dyn Mesh3D>> = vec![Box::new(IndexedMesh {
+        positions: corners.to_vec(),
+        triangles: vec![(0, 1, 2)],
+        ..Default::default()
+    })];
+    let ids = Rasterizer::new(WIDTH, HEIGHT).pick_buffer(&camera, &objects);
+
+    let mut picks = 0;
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            if let Some(pick) = ids.query(x, y) {
+                picks += 1;
+                let center = Vec2::new(x as f32 + 0.5f32, y as f32 + 0.5f32);
+                assert_on_triangle(&camera, corners, pick, center);
+            }
+        }
+    }
+    assert!(picks > 100, "{picks} picks");
+}
@@ -0,0 +1,129 @@
+use glam::Vec3;
+use pixel_renderer::{mesh::IndexedMesh, primitives::*};
+
+/// Signed volume enclosed by the triangles, positive if they face outwards
+fn volume(mesh: &IndexedMesh) -> f32 {
+    mesh.triangles
+        .iter()
+        .map(|t| {
+            let (a, b, c) = (
+                mesh.positions[t.0],
+                mesh.positions[t.1],
+                mesh.positions[t.2],
+            );
+            a.dot(b.cross(c)) / 6f32
+        })
+        .sum()
+}
+
+fn check_attributes(mesh: &IndexedMesh) {
+    let count = mesh.positions.len();
+    assert_eq!(mesh.normals.len(), count);
+    assert_eq!(mesh.tex_coords.len(), count);
+    assert_eq!(mesh.colors.len(), count);
+    for t in &mesh.triangles {
+        // No slivers at the poles, and vertex normals on the same side as the triangle
+        let face = mesh.face_normal(*t);
+        assert_ne!(face, Vec3::ZERO);
+        assert!(face.dot(mesh.normals[t.0] + mesh.normals[t.1] + mesh.normals[t.2]) > 0f32);
+    }
+    for uv in &mesh.tex_coords {
+        assert!(uv.x >= 0f32 && uv.y >= 0f32 && uv.y <= 1f32);
+    }
+}
+
+fn check_closed(mesh: &IndexedMesh, expected_volume: f32) {
+    check_attributes(mesh);
+    let volume = volume(mesh);
+    assert!(
+        (volume - expected_volume).abs() < expected_volume * 0.02f32,
+        "{volume} != {expected_volume}"
+    );
+}
+
+#[test]
+fn plane_faces_up() {
+    let mesh = Plane {
+        subdivisions: (3, 2),
+        ..Default::default()
+    }
+    .mesh();
+    check_attributes(&mesh);
+    assert_eq!(mesh.positions.len(), 4 * 3);
+    assert_eq!(mesh.triangles.len(), 2 * 3 * 2);
+    assert!(mesh
+        .triangles
+        .iter()
+        .all(|t| mesh.face_normal(*t) == Vec3::Y));
+}
+
+#[test]
+fn closed_primitives_face_outwards() {
+    use std::f32::consts::PI;
+
+    check_closed(&Cube::default().mesh(), 1f32);
+    check_closed(
+        &Cube {
+            size: Vec3::new(1f32, 2f32, 3f32),
+            subdivisions: 4,
+            ..Default::default()
+        }
+        .mesh(),
+        6f32,
+    );
+    let sphere = 4f32 / 3f32 * PI * 0.5f32.powi(3);
+    check_closed(
+        &UvSphere {
+            segments: 64,
+            rings: 32,
+            ..Default::default()
+        }
+        .mesh(),
+        sphere,
+    );
+    check_closed(
+        &Icosphere {
+            subdivisions: 4,
+            ..Default::default()
+        }
+        .mesh(),
+        sphere,
+    );
+    check_closed(&Cylinder::default().mesh(), PI * 0.25f32);
+    check_closed(&Cone::default().mesh(), PI * 0.25f32 / 3f32);
+    check_closed(
+        &Torus {
+            segments: 64,
+            sides: 32,
+            ..Default::default()
+        }
+        .mesh(),
+        2f32 * PI * PI * 0.35f32 * 0.15f32 * 0.15f32,
+    );
+    check_closed(
+        &Capsule::default().mesh(),
+        PI * 0.25f32.powi(2) * 0.5f32 + 4f32 / 3f32 * PI * 0.25f32.powi(3),
+    );
+}
+
+#[test]
+fn subdivisions_add_vertices() {
+    let coarse = UvSphere {
+        segments: 8,
+        rings: 4,
+        ..Default::default()
+    }
+    .mesh();
+    // Quads touching the poles are single triangles
+    assert_eq!(coarse.triangles.len(), 8 * 4 * 2 - 2 * 8);
+    assert_eq!(
+        Icosphere {
+            subdivisions: 1,
+            ..Default::default()
+        }
+        .mesh()
+        .triangles
+        .len(),
+        80
+    );
+}
@@ -0,0 +1,157 @@
+use glam::{Vec2, Vec3};
+use pixel_renderer::{
+    camera::{Camera, FitStrategy, Projection},
+    mesh::IndexedMesh,
+    renderer::{DepthBuffer, Mesh3D, Rasterizer},
+};
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 48;
+
+/// Twice the signed area of `a`, `b`, `p` on the screen, positive inside a visible triangle
+fn edge(a: Vec2, b: Vec2, p: Vec2) -> f32 {
+    (b.y - a.y) * (p.x - a.x) - (b.x - a.x) * (p.y - a.y)
+}
+
+#[test]
+fn projected_points_agree_with_drawn_pixels() {
+    let mut camera = Camera {
+        position: Vec3::new(0.5f32, 1f32, 2f32),
+        far: 20f32,
+        focal_length: 25f32,
+        ..Default::default()
+    };
+    camera.look_at(Vec3::new(0f32, 0f32, -3f32), Vec3::Y);
+    // Tilted away from the camera so the depth changes across the triangle
+    let corners = [
+        Vec3::new(-1.5f32, -0.5f32, -2f32),
+        Vec3::new(1.5f32, -0.8f32, -3f32),
+        Vec3::new(0f32, 1.2f32, -5f32),
+    ];
+    let triangle = IndexedMesh {
+        positions: corners.to_vec(),
+        triangles: vec![(0, 1, 2)],
+        ..Default::default()
+    };
+    let rasterizer = Rasterizer::new(WIDTH, HEIGHT);
+    let objects: Vec<Box<dyn Mesh3D>> = vec![Box::new(triangle)];
+    let depth: DepthBuffer = rasterizer.depth_buffer(&camera, &objects);
+
+    let dimensions = (WIDTH, HEIGHT);
+    let view_projection = camera.view_projection(dimensions);
+    let projected = corners.map(|c| {
+        let p = Camera::project_with(&view_projection, c, dimensions);
+        assert_eq!(p, camera.project(c, dimensions));
+        p.truncate()
+    });
+    let normal = (corners[1] - corners[0]).cross(corners[2] - corners[0]);
+
+    let mut drawn = 0;
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let center = Vec2::new(x as f32 + 0.5f32, y as f32 + 0.5f32);
+            let inside = [(0, 1), (1, 2), (2, 0)].map(|(a, b)| {
+                let e = edge(projected[a], projected[b], center);
+                // Centers closer to an edge than the snapping of the rasterizer may go either way
+                let margin = 0.02f32 * projected[a].distance(projected[b]);
+                (e > margin, e < -margin)
+            });
+            let value = depth.get(x, y);
+            if inside.iter().all(|(inside, _)| *inside) {
+                assert!(value.is_some(), "({x}, {y}) is not drawn");
+            } else if inside.iter().any(|(_, outside)| *outside) {
+                assert!(
+                    value.is_none(),
+                    "({x}, {y}) is drawn outside of the triangle"
+                );
+            }
+            let Some(value) = value else {
+                continue;
+            };
+            drawn += 1;
+
+            // The point drawn at the pixel is on the triangle and projects back onto the pixel
+            let point = camera.unproject(center, value, dimensions);
+            let distance = (point - corners[0]).dot(normal.normalize());
+            assert!(distance.abs() < 1e-3f32, "{point} is off the triangle");
+            let back = Camera::project_with(&view_projection, point, dimensions);
+            assert!(
+                back.truncate().abs_diff_eq(center, 1e-3f32),
+                "{back} at {center}"
+            );
+            assert!(
+                (back.z - value).abs() < 1e-5f32,
+                "{} instead of {value}",
+                back.z
+            );
+        }
+    }
+    assert!(drawn > 100, "{drawn} pixels drawn");
+}
+
+#[test]
+fn dolly_zoom_keeps_the_focus_plane_in_place() {
+    let mut camera = Camera {
+        position: Vec3::new(0.5f32, 1f32, 2f32),
+        ..Default::default()
+    };
+    camera.look_at(Vec3::new(0f32, 0f32, -3f32), Vec3::Y);
+    let focus_distance = 4f32;
+    let dimensions = (WIDTH, HEIGHT);
+    // A point on the focus plane and one behind it
+    let on_plane =
+        camera.position + camera.forward() * focus_distance + camera.right() + camera.up() * 0.5f32;
+    let behind = on_plane + camera.forward() * 2f32;
+    let expected = camera.project(on_plane, dimensions).truncate();
+    let behind_start = camera.project(behind, dimensions).truncate();
+
+    let mut focal_length = camera.focal_length;
+    for t in [0.25f32, 0.5f32, 0.75f32, 0.9f32, 1f32] {
+        let zoomed = camera.dolly_zoom(focus_distance, t);
+        let p = zoomed.project(on_plane, dimensions).truncate();
+        assert!(
+            p.abs_diff_eq(expected, 1e-2f32),
+            "{p} instead of {expected} at {t}"
+        );
+        // The field of view narrows, so points behind the plane shrink less towards the center
+        if t < 1f32 {
+            assert!(zoomed.focal_length > focal_length);
+            focal_length = zoomed.focal_length;
+        }
+        let b = zoomed.project(behind, dimensions).truncate();
+        assert!(
+            b.distance(expected) < behind_start.distance(expected),
+            "{b} at {t}"
+        );
+    }
+}
+
+#[test]
+fn squares_stay_square_for_any_output_shape() {
+    for fit_strategy in [FitStrategy::Fill, FitStrategy::Overscan] {
+        for projection in [
+            Projection::Perspective,
+            Projection::Orthographic { height: 4f32 },
+        ] {
+            let mut camera = Camera {
+                fit_strategy,
+                projection,
+                ..Default::default()
+            };
+            camera.look_at(Vec3::NEG_Z, Vec3::Y);
+            // Wider and narrower than the aperture
+            for dimensions in [(128, 48), (48, 64), (35, 24)] {
+                let corner = |x: f32, y: f32| {
+                    camera
+                        .project(Vec3::new(x, y, -3f32), dimensions)
+                        .truncate()
+                };
+                let size = corner(0.5f32, 0.5f32) - corner(-0.5f32, -0.5f32);
+                assert!(
+                    (size.x.abs() - size.y.abs()).abs() < 1e-3f32 * size.x.abs(),
+                    "{size} for {dimensions:?} with {fit_strategy:?} and {projection:?}"
+                );
+            }
+        }
+    }
+}
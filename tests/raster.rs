@@ -0,0 +1,116 @@
+use glam::Vec3;
+use palette::Srgba;
+use pixel_renderer::{
+    camera::{Camera, Projection},
+    mesh::IndexedMesh,
+    renderer::{Mesh3D, Rasterizer, Renderer},
+};
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 48;
+
+/// Draws `mesh` in translucent white over black, seen along -z with one unit per pixel
+fn render(mesh: IndexedMesh) -> Vec<u8> {
+    let mut camera = Camera {
+        projection: Projection::Orthographic {
+            height: HEIGHT as f32,
+        },
+        aperture: (WIDTH as u8, HEIGHT as u8),
+        ..Default::default()
+    };
+    camera.look_at(Vec3::NEG_Z, Vec3::Y);
+    let colors = vec![Srgba::new(1f32, 1f32, 1f32, 0.5f32); mesh.positions.len()];
+    let objects: Vec<Box<dyn Mesh3D>> = vec![Box::new(IndexedMesh { colors, ..mesh })];
+    let rasterizer = Rasterizer::new(WIDTH, HEIGHT);
+    let mut buffer = vec![0u8; (WIDTH * HEIGHT * 4) as usize];
+    let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+    rasterizer.render(&camera, &objects, &mut frame);
+    buffer
+}
+
+/// Asserts that every pixel was blended exactly once, twice would be about 191
+fn assert_covered_once(buffer: &[u8]) {
+    for (i, pixel) in buffer.chunks_exact(4).enumerate() {
+        let (x, y) = (i as u32 % WIDTH, i as u32 / WIDTH);
+        assert!(
+            pixel[..3].iter().all(|c| (126..=129).contains(c)),
+            "{pixel:?} at ({x}, {y})"
+        );
+    }
+}
+
+/// Counter-clockwise triangles of a grid of quads
+fn grid_triangles(columns: usize, rows: usize) -> Vec<(usize, usize, usize)> {
+    let index = |x: usize, y: usize| y * (columns + 1) + x;
+    (0..rows)
+        .flat_map(|y| (0..columns).map(move |x| (x, y)))
+        .flat_map(|(x, y)| {
+            let (a, b, c, d) = (
+                index(x, y),
+                index(x + 1, y),
+                index(x + 1, y + 1),
+                index(x, y + 1),
+            );
+            [(a, b, c), (a, c, d)]
+        })
+        .collect()
+}
+
+#[test]
+fn shared_edges_are_covered_once() {
+    // A grid reaching past the output, with inner vertices moved off the pixel grid
+    let (columns, rows) = (9, 7);
+    let mut positions = vec![];
+    for y in 0..=rows {
+        for x in 0..=columns {
+            let mut p = Vec3::new(x as f32 * 9f32 - 40f32, y as f32 * 9f32 - 32f32, -5f32);
+            if (1..columns).contains(&x) && (1..rows).contains(&y) {
+                p.x += ((x * 7 + y * 3) % 5) as f32 * 0.83f32 - 1.5f32;
+                p.y += ((x * 5 + y * 11) % 7) as f32 * 0.61f32 - 1.7f32;
+            }
+            positions.push(p);
+        }
+    }
+    assert_covered_once(&render(IndexedMesh {
+        positions,
+        triangles: grid_triangles(columns, rows),
+        ..Default::default()
+    }));
+
+    // A fan around a vertex in the middle of a pixel edge, with spokes at many angles
+    let spokes = 23;
+    let mut positions = vec![Vec3::new(0.5f32, 0f32, -5f32)];
+    positions.extend((0..spokes).map(|i| {
+        let angle = i as f32 / spokes as f32 * std::f32::consts::TAU;
+        Vec3::new(angle.cos() * 100f32, angle.sin() * 100f32, -5f32)
+    }));
+    let triangles = (0..spokes)
+        .map(|i| (0, i + 1, (i + 1) % spokes + 1))
+        .collect();
+    assert_covered_once(&render(IndexedMesh {
+        positions,
+        triangles,
+        ..Default::default()
+    }));
+}
+
+#[test]
+fn clockwise_triangles_are_culled() {
+    let positions = vec![
+        Vec3::new(-100f32, -100f32, -5f32),
+        Vec3::new(100f32, -100f32, -5f32),
+        Vec3::new(0f32, 100f32, -5f32),
+    ];
+    let drawn = render(IndexedMesh {
+        positions: positions.clone(),
+        triangles: vec![(0, 1, 2)],
+        ..Default::default()
+    });
+    assert_ne!(drawn[..3], [0; 3]);
+    let culled = render(IndexedMesh {
+        positions,
+        triangles: vec![(0, 2, 1)],
+        ..Default::default()
+    });
+    assert_eq!(culled[..3], [0; 3]);
+}
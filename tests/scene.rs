@@ -0,0 +1,74 @@
+use palette::Srgba;
+use pixel_renderer::{
+    drawing::{BresenhamCircle, Circle, Shape2D},
+    renderer::{Drawifier, Scene2D},
+};
+
+const WIDTH: u32 = 32;
+const HEIGHT: u32 = 32;
+
+fn circle(color: Srgba) -> Shape2D {
+    BresenhamCircle::new((16, 16), 8, color).collect()
+}
+
+/// Color of a pixel on the circles
+fn top_color(scene: &Scene2D) -> [u8; 4] {
+    let drawifier = Drawifier {
+        output_width: WIDTH,
+        output_height: HEIGHT,
+        background: Default::default(),
+    };
+    let mut buffer = vec![0u8; (WIDTH * HEIGHT * 4) as usize];
+    let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+    drawifier.render_scene(scene, &mut frame);
+    frame[(16 * WIDTH + 24) as usize].try_into().unwrap()
+}
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+#[test]
+fn stale_handles_are_ignored() {
+    let mut scene = Scene2D::new();
+    let red = scene.add(circle(Srgba::new(1f32, 0f32, 0f32, 1f32)));
+    assert!(scene.remove(red).is_some());
+    assert!(scene.remove(red).is_none());
+    assert!(scene.get(red).is_none());
+    assert!(scene.is_empty());
+
+    // The next shape takes over the free slot, the old handle still does not reach it
+    let blue = scene.add(circle(Srgba::new(0f32, 0f32, 1f32, 1f32)));
+    assert_ne!(blue, red);
+    assert_eq!(scene.len(), 1);
+    assert!(scene.get(red).is_none());
+    assert!(scene.update(red, circle(Srgba::default())).is_none());
+    scene.set_visible(red, false);
+    scene.set_z(red, 5);
+    assert!(scene.remove(red).is_none());
+    let node = scene.get(blue).unwrap();
+    assert!(node.visible);
+    assert_eq!(node.z, 0);
+    assert_eq!(top_color(&scene), BLUE);
+}
+
+#[test]
+fn higher_z_is_drawn_on_top() {
+    let mut scene = Scene2D::new();
+    let red = scene.add(circle(Srgba::new(1f32, 0f32, 0f32, 1f32)));
+    let blue = scene.add(circle(Srgba::new(0f32, 0f32, 1f32, 1f32)));
+    // Shapes with the same z are drawn in the order they were added
+    assert_eq!(top_color(&scene), BLUE);
+
+    scene.set_z(red, 1);
+    assert_eq!(top_color(&scene), RED);
+    scene.set_z(blue, 2);
+    assert_eq!(top_color(&scene), BLUE);
+    scene.set_z(blue, -1);
+    assert_eq!(top_color(&scene), RED);
+
+    scene.bring_to_front(blue);
+    assert_eq!(top_color(&scene), BLUE);
+    scene.send_to_back(blue);
+    assert_eq!(top_color(&scene), RED);
+    assert!(scene.get(blue).unwrap().z < scene.get(red).unwrap().z);
+}
@@ -0,0 +1,162 @@
+use glam::{Vec2, Vec3};
+use palette::Srgba;
+use pixel_renderer::{
+    formats::{read_ply, read_stl, write_ply, write_stl, PlyFormat, PlyLoadError, StlFormat},
+    mesh::IndexedMesh,
+};
+
+fn quad() -> IndexedMesh {
+    IndexedMesh {
+        positions: vec![
+            Vec3::new(0f32, 0f32, 0f32),
+            Vec3::new(1f32, 0f32, 0f32),
+            Vec3::new(1f32, 1f32, 0f32),
+            Vec3::new(0f32, 1f32, 0f32),
+        ],
+        normals: vec![Vec3::Z; 4],
+        tex_coords: vec![
+            Vec2::new(0f32, 0f32),
+            Vec2::new(1f32, 0f32),
+            Vec2::new(1f32, 1f32),
+            Vec2::new(0f32, 1f32),
+        ],
+        colors: vec![
+            Srgba::new(1f32, 0f32, 0f32, 1f32),
+            Srgba::new(0f32, 1f32, 0f32, 1f32),
+            Srgba::new(0f32, 0f32, 1f32, 1f32),
+            Srgba::new(1f32, 1f32, 1f32, 0f32),
+        ],
+        triangles: vec![(0, 1, 2), (0, 2, 3)],
+        extra: vec![],
+        texture: None,
+    }
+}
+
+#[test]
+fn stl_round_trips() {
+    let mesh = quad();
+    for format in [StlFormat::Ascii, StlFormat::Binary] {
+        let mut bytes = vec![];
+        write_stl(&mesh, format, &mut bytes).unwrap();
+        let read = read_stl(&bytes[..]).unwrap();
+
+        // STL does not share vertices
+        assert_eq!(read.triangles, vec![(0, 1, 2), (3, 4, 5)]);
+        let corners: Vec<Vec3> = [0, 1, 2, 0, 2, 3]
+            .iter()
+            .map(|i| mesh.positions[*i])
+            .collect();
+        assert_eq!(read.positions, corners);
+        assert_eq!(read.normals, vec![Vec3::Z; 6]);
+        assert!(read.colors.is_empty() && read.tex_coords.is_empty());
+    }
+}
+
+#[test]
+fn binary_stl_may_start_with_solid() {
+    let mut bytes = vec![];
+    write_stl(&quad(), StlFormat::Binary, &mut bytes).unwrap();
+    bytes[..6].copy_from_slice(b"solid ");
+    assert_eq!(read_stl(&bytes[..]).unwrap().triangles.len(), 2);
+
+    bytes.pop();
+    assert!(read_stl(&bytes[..]).is_err());
+}
+
+#[test]
+fn ascii_stl_computes_missing_normals() {
+    let text = "solid test
+  facet normal 0 0 0
+    outer loop
+      vertex 0 0 0
+      vertex 0 1 0
+      vertex 1 0 0
+    endloop
+  endfacet
+endsolid test
+";
+    let mesh = read_stl(text.as_bytes()).unwrap();
+    assert_eq!(mesh.normals, vec![Vec3::NEG_Z; 3]);
+}
+
+#[test]
+fn ply_round_trips() {
+    let mesh = quad();
+    for format in [
+        PlyFormat::Ascii,
+        PlyFormat::BinaryLittleEndian,
+        PlyFormat::BinaryBigEndian,
+    ] {
+        let mut bytes = vec![];
+        write_ply(&mesh, format, &mut bytes).unwrap();
+        assert_eq!(read_ply(&bytes[..]).unwrap(), mesh, "{format:?}");
+    }
+}
+
+#[test]
+fn reads_ply_polygons_and_skips_unknown_elements() {
+    let text = "ply
+format ascii 1.0
+comment made by hand
+element vertex 4
+property double x
+property double y
+property double z
+property ushort red
+property ushort green
+property ushort blue
+element material 1
+property list uchar float values
+element face 1
+property uchar flags
+property list uchar int vertex_index
+end_header
+0 0 0 65535 0 0
+1 0 0 0 65535 0
+1 1 0 0 0 65535
+0 1 0 0 0 0
+2 0.5 0.5
+7 4 0 1 2 3
+";
+    let mesh = read_ply(text.as_bytes()).unwrap();
+    assert_eq!(mesh.positions.len(), 4);
+    assert!(mesh.normals.is_empty() && mesh.tex_coords.is_empty());
+    assert_eq!(mesh.colors[1], Srgba::new(0f32, 1f32, 0f32, 1f32));
+    assert_eq!(mesh.triangles, vec![(0, 1, 2), (0, 2, 3)]);
+}
+
+#[test]
+fn rejects_faces_of_missing_vertices() {
+    let text = "ply
+format ascii 1.0
+element vertex 1
+property float x
+property float y
+property float z
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0
+3 0 1 2
+";
+    let error = read_ply(text.as_bytes()).unwrap_err();
+    assert!(matches!(error, PlyLoadError::Data(_)));
+
+    // Negative and fractional indices are not rounded to some vertex
+    for face in ["3 0 -1 0", "3 0 0.5 0"] {
+        let error = read_ply(text.replace("3 0 1 2", face).as_bytes()).unwrap_err();
+        assert!(matches!(error, PlyLoadError::Data(_)), "{face}");
+    }
+    let error = read_ply(text.replace("3 0 1 2", "3 0 0 -1").as_bytes()).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid PLY data: -1 is not a valid count or index"
+    );
+    assert!(read_ply(text.replace("3 0 1 2", "3 0 0 0").as_bytes()).is_ok());
+
+    let error = read_ply(&b"ply\nformat ascii 2.0\nend_header\n"[..]).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid PLY header at line 2: version 2.0 is not supported"
+    );
+}
@@ -0,0 +1,76 @@
+use glam::Vec3;
+use palette::Srgba;
+use pixel_renderer::{
+    camera::Camera,
+    mesh::IndexedMesh,
+    renderer::{Mesh3D, Rasterizer, Renderer, Transparency},
+};
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 48;
+
+/// A translucent square around `center`, facing a camera looking along -z
+fn square(center: Vec3, color: Srgba) -> IndexedMesh {
+    let corners = [(-1f32, -1f32), (1f32, -1f32), (1f32, 1f32), (-1f32, 1f32)];
+    IndexedMesh {
+        positions: corners
+            .iter()
+            .map(|(x, y)| center + Vec3::new(*x, *y, 0f32))
+            .collect(),
+        colors: vec![color; 4],
+        triangles: vec![(0, 1, 2), (0, 2, 3)],
+        ..Default::default()
+    }
+}
+
+fn render(objects: Vec<IndexedMesh>) -> Vec<u8> {
+    let mut camera = Camera {
+        focal_length: 20f32,
+        ..Default::default()
+    };
+    camera.look_at(Vec3::NEG_Z, Vec3::Y);
+    let objects: Vec<Box<dyn Mesh3D>> = objects
+        .into_iter()
+        .map(|o| Box::new(o) as Box<dyn Mesh3D>)
+        .collect();
+    let rasterizer = Rasterizer {
+        transparency: Transparency::WeightedBlended,
+        ..Rasterizer::new(WIDTH, HEIGHT)
+    };
+    let mut buffer = vec![0u8; (WIDTH * HEIGHT * 4) as usize];
+    let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+    rasterizer.render(&camera, &objects, &mut frame);
+    buffer
+}
+
+#[test]
+fn weighted_blending_does_not_depend_on_the_order() {
+    let squares = [
+        square(
+            Vec3::new(-0.4f32, 0f32, -5f32),
+            Srgba::new(1f32, 0f32, 0f32, 0.5f32),
+        ),
+        square(
+            Vec3::new(0f32, 0.3f32, -6f32),
+            Srgba::new(0f32, 1f32, 0f32, 0.4f32),
+        ),
+        square(
+            Vec3::new(0.4f32, -0.2f32, -7f32),
+            Srgba::new(0f32, 0f32, 1f32, 0.6f32),
+        ),
+    ];
+    let expected = render(squares.to_vec());
+    // Where all three overlap every color shows
+    let center = &expected[((HEIGHT / 2 * WIDTH + WIDTH / 2) * 4) as usize..][..3];
+    assert!(center.iter().all(|c| *c > 0), "{center:?}");
+
+    for order in [[0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]] {
+        let mut objects: Vec<_> = order.iter().map(|i| squares[*i].clone()).collect();
+        // The order of triangles within a mesh does not matter either
+        objects[0].triangles.reverse();
+        let frame = render(objects);
+        for (a, b) in frame.iter().zip(&expected) {
+            assert!(a.abs_diff(*b) <= 1, "{a} instead of {b} for {order:?}");
+        }
+    }
+}
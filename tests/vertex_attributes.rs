@@ -0,0 +1,126 @@
+use std::sync::{Arc, Mutex};
+
+use glam::{Vec2, Vec3};
+use palette::Srgba;
+use pixel_renderer::{
+    camera::Camera,
+    mesh::IndexedMesh,
+    primitives::Plane,
+    renderer::{AttributeBuffer, DepthBuffer, Mesh3D, Rasterizer, Renderer, Semantic},
+};
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 48;
+
+/// Looks down at a floor stretching into the distance, where perspective matters most
+fn floor() -> (Camera, IndexedMesh) {
+    let mut camera = Camera {
+        position: Vec3::new(0f32, 1f32, 0f32),
+        far: 100f32,
+        ..Default::default()
+    };
+    camera.look_at(Vec3::new(0f32, 0f32, -4f32), Vec3::Y);
+    let mut floor = Plane {
+        size: Vec2::new(8f32, 20f32),
+        ..Default::default()
+    }
+    .mesh();
+    for p in &mut floor.positions {
+        p.z -= 10f32;
+    }
+    (camera, floor)
+}
+
+#[test]
+fn interpolates_positions_with_perspective() {
+    let (camera, floor) = floor();
+    let fragments = Arc::new(Mutex::new(vec![]));
+    let recorded = fragments.clone();
+    let rasterizer = Rasterizer {
+        fragment_shader: Some(Arc::new(move |f| {
+            recorded
+                .lock()
+                .unwrap()
+                .push((f.x, f.y, f.position().unwrap()));
+            Srgba::new(1f32, 1f32, 1f32, 1f32)
+        })),
+        ..Rasterizer::new(WIDTH, HEIGHT)
+    };
+
+    let objects: Vec<Box<dyn Mesh3D>> = vec![Box::new(floor)];
+    let mut buffer = vec![0u8; (WIDTH * HEIGHT * 4) as usize];
+    let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+    let mut depth = DepthBuffer::new(WIDTH, HEIGHT);
+    rasterizer.render_with_depth(&camera, &objects, &mut frame, &mut depth);
+
+    let fragments = fragments.lock().unwrap();
+    assert!(fragments.len() > (WIDTH * HEIGHT / 4) as usize);
+    for (x, y, position) in fragments.iter() {
+        // The point at the center of the pixel, found from its depth alone
+        let center = Vec2::new(*x as f32 + 0.5f32, *y as f32 + 0.5f32);
+        let expected = camera.unproject(
+            center,
+            depth.get(*x as u32, *y as u32).unwrap(),
+            (WIDTH, HEIGHT),
+        );
+        assert!(
+            position.abs_diff_eq(expected, 1e-3f32 * expected.length()),
+            "{position} != {expected} at ({x}, {y})"
+        );
+    }
+}
+
+#[test]
+fn hands_every_attribute_to_the_shader() {
+    let (camera, mut floor) = floor();
+    // Custom attribute holding the distance of every vertex to the camera,
+    // named at runtime like the attributes read from a file
+    let name = String::from("distance");
+    let distances = floor
+        .positions
+        .iter()
+        .map(|p| p.distance(camera.position))
+        .collect();
+    floor.extra = vec![
+        (
+            Semantic::Custom(name.into()),
+            AttributeBuffer::Float(distances),
+        ),
+        (
+            Semantic::TexCoord(1),
+            AttributeBuffer::Vec2(vec![Vec2::new(0.25f32, 0.75f32); 4]),
+        ),
+    ];
+    let rasterizer = Rasterizer {
+        fragment_shader: Some(Arc::new(|f| {
+            assert_eq!(f.normal(), Some(Vec3::Y));
+            let uv = f.tex_coord(1).unwrap();
+            assert!(uv.abs_diff_eq(Vec2::new(0.25f32, 0.75f32), 1e-5f32));
+            assert!(f.attribute(Semantic::Custom("distance".into())).is_some());
+            assert_eq!(f.attribute(Semantic::Tangent), None);
+            let uv = f.tex_coord(0).unwrap();
+            Srgba::new(uv.x, uv.y, 0f32, 1f32)
+        })),
+        ..Rasterizer::new(WIDTH, HEIGHT)
+    };
+
+    let objects: Vec<Box<dyn Mesh3D>> = vec![Box::new(floor)];
+    let mut buffer = vec![0u8; (WIDTH * HEIGHT * 4) as usize];
+    let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+    rasterizer.render(&camera, &objects, &mut frame);
+
+    // v grows away from the camera, so it is larger at the top of the image
+    let green = |y: u32| frame[(y * WIDTH + WIDTH / 2) as usize][1];
+    assert!(green(HEIGHT / 2 + 2) > green(HEIGHT - 1));
+}
+
+#[test]
+fn meshes_without_colors_are_white() {
+    let (camera, mut floor) = floor();
+    floor.colors.clear();
+    let objects: Vec<Box<dyn Mesh3D>> = vec![Box::new(floor)];
+    let mut buffer = vec![0u8; (WIDTH * HEIGHT * 4) as usize];
+    let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+    Rasterizer::new(WIDTH, HEIGHT).render(&camera, &objects, &mut frame);
+    assert_eq!(frame[(WIDTH * HEIGHT - 1) as usize], [255, 255, 255, 255]);
+}
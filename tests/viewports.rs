@@ -0,0 +1,68 @@
+use glam::Vec3;
+use palette::Srgba;
+use pixel_renderer::{
+    camera::Camera,
+    drawing::Rect,
+    mesh::IndexedMesh,
+    renderer::{Mesh3D, Rasterizer, Viewport},
+};
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 48;
+
+/// A large square `z` away along the z axis, facing the origin
+fn square(z: f32, color: Srgba) -> IndexedMesh {
+    let corners = [
+        (-100f32, -100f32),
+        (100f32, -100f32),
+        (100f32, 100f32),
+        (-100f32, 100f32),
+    ];
+    let triangles = if z < 0f32 {
+        vec![(0, 1, 2), (0, 2, 3)]
+    } else {
+        vec![(0, 2, 1), (0, 3, 2)]
+    };
+    IndexedMesh {
+        positions: corners.iter().map(|(x, y)| Vec3::new(*x, *y, z)).collect(),
+        colors: vec![color; 4],
+        triangles,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn viewports_draw_their_own_camera_into_their_own_rect() {
+    let objects: Vec<Box<dyn Mesh3D>> = vec![
+        Box::new(square(-5f32, Srgba::new(1f32, 0f32, 0f32, 1f32))),
+        Box::new(square(5f32, Srgba::new(0f32, 0f32, 1f32, 1f32))),
+    ];
+    let viewport = |target: Vec3, rect: Rect| {
+        let mut camera = Camera::default();
+        camera.look_at(target, Vec3::Y);
+        Viewport { camera, rect }
+    };
+    // A gap between the viewports and a row below them that neither covers
+    let viewports = [
+        viewport(Vec3::NEG_Z, Rect::new(0, 0, 28, HEIGHT as i32 - 1)),
+        viewport(Vec3::Z, Rect::new(36, 0, WIDTH as i32, HEIGHT as i32 - 1)),
+    ];
+
+    let sentinel = [1, 2, 3, 4];
+    let mut buffer = sentinel.repeat((WIDTH * HEIGHT) as usize);
+    let mut frame: Vec<&mut [u8]> = buffer.chunks_exact_mut(4).collect();
+    Rasterizer::new(WIDTH, HEIGHT).render_viewports(&viewports, &objects, &mut frame);
+
+    let pixel = |x: u32, y: u32| &buffer[((y * WIDTH + x) * 4) as usize..][..4];
+    // Each camera sees the square in front of it
+    assert_eq!(pixel(14, 24), [255, 0, 0, 255]);
+    assert_eq!(pixel(50, 24), [0, 0, 255, 255]);
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let inside = viewports
+                .iter()
+                .any(|v| v.rect.contains(x as i32, y as i32));
+            assert_eq!(pixel(x, y) != sentinel, inside, "({x}, {y})");
+        }
+    }
+}